@@ -0,0 +1,227 @@
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+use crate::challenge::{
+    ChallengeCase, ChallengeCommand, ChallengeConfig, ChallengeExpectation, ChallengeParseError,
+    ConfigFormat, OutputExpectation, StringReference,
+};
+use crate::errors::{BlessError, ProgramError};
+use crate::{find_challenge, parse_found, ChallengeTarget, Found};
+
+/// Matchers that compare against the literal output, so it can be recorded.
+const BLESSABLE_MATCHERS: [&str; 2] = ["exact", "trimmed"];
+
+/// What blessing did with a case's actual output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BlessOutcome {
+    /// Written to the file its expectation refers to.
+    WroteFile(PathBuf),
+    /// Written into the `expected` block of the challenge file.
+    UpdatedConfig,
+    /// Left alone, for the given reason.
+    Skipped(String),
+}
+
+/// A challenge file, or a file it includes, whose expectations are being
+/// updated.
+struct BlessedFile {
+    source: String,
+    format: ConfigFormat,
+    document: serde_yaml::Value,
+    edited: bool,
+}
+impl BlessedFile {
+    fn read(path: &Path) -> Result<Self, ProgramError> {
+        let source = read_to_string(path).map_err(BlessError::CouldNotReadFile)?;
+        let format = ConfigFormat::of_file(path);
+        let document = format
+            .parse(&source)
+            .map_err(|diagnostic| ChallengeParseError::from(diagnostic).in_file(path))?;
+        Ok(Self {
+            source,
+            format,
+            document,
+            edited: false,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BlessedCase {
+    pub parent_path: Vec<String>,
+    pub name: String,
+    pub outcome: BlessOutcome,
+}
+
+/// Bless a single case, selected like [`crate::run_challenge`].
+pub fn bless_challenge<T: Into<ChallengeTarget>>(
+    target: T,
+    cases: Vec<String>,
+) -> Result<BlessedCase, ProgramError> {
+    let found = find_challenge(target.into())?;
+    let (command, case) = parse_found(&found)?.select_case(&cases)?;
+    let mut blessed = bless_cases(&found, vec![(command, case)])?;
+    Ok(blessed.remove(0))
+}
+
+/// Run the selected cases and record their actual stdout as the expected
+/// output, in whichever file declared them. Cases that can't run, or whose
+/// exit status doesn't match their expectation, are skipped. Nothing is
+/// written until every case has run.
+pub fn bless_challenges<T: Into<ChallengeTarget>>(
+    target: T,
+    cases: Vec<String>,
+) -> Result<Vec<BlessedCase>, ProgramError> {
+    let found = find_challenge(target.into())?;
+    let cases = parse_found(&found)?.select_cases(&cases)?;
+    bless_cases(&found, cases)
+}
+
+fn bless_cases(
+    found: &Found,
+    cases: Vec<(ChallengeCommand, ChallengeCase)>,
+) -> Result<Vec<BlessedCase>, ProgramError> {
+    let challenge_dir = found.dir();
+    let mut files = IndexMap::new();
+    let mut outputs = Vec::new();
+
+    let mut blessed = Vec::new();
+    for (command, case) in cases {
+        let expected = case.expected().cloned().unwrap_or_default();
+        let outcome = match case.run(challenge_dir, &command) {
+            Err(err) => BlessOutcome::Skipped(format!("it couldn't run: {err}")),
+            Ok(output) if !expected.exit_code.matches(&output.status) => {
+                BlessOutcome::Skipped(format!(
+                    "unexpected {}, expected {}",
+                    output.status, expected.exit_code
+                ))
+            }
+            Ok(output) => bless_output(
+                found,
+                &case,
+                expected,
+                output.stdout,
+                &mut files,
+                &mut outputs,
+            )?,
+        };
+
+        blessed.push(BlessedCase {
+            parent_path: case.parent_path().to_vec(),
+            name: case.name,
+            outcome,
+        });
+    }
+
+    let edited = files
+        .into_iter()
+        .filter(|(_, file)| file.edited)
+        .collect::<Vec<_>>();
+    for (path, file) in &edited {
+        if matches!(found, Found::File { ref file, .. } if file == path) {
+            ChallengeConfig::parse_str_as(&file.source, file.format, challenge_dir).map(|_| ())
+        } else {
+            ChallengeConfig::validate_str(&file.source, file.format)
+        }
+        .map_err(|err| BlessError::InvalidResult(err.in_file(path)))?;
+    }
+    let edited = edited.into_iter().map(|(path, file)| (path, file.source));
+    for (path, content) in outputs.into_iter().chain(edited) {
+        write(&path, content).map_err(|err| BlessError::CouldNotWriteFile(path.clone(), err))?;
+    }
+
+    Ok(blessed)
+}
+
+/// Record `output` as the expected stdout of `case`. Edits to challenge files
+/// are made in `files`, and output files to write are added to `outputs`.
+fn bless_output(
+    found: &Found,
+    case: &ChallengeCase,
+    expected: ChallengeExpectation,
+    output: String,
+    files: &mut IndexMap<PathBuf, BlessedFile>,
+    outputs: &mut Vec<(PathBuf, String)>,
+) -> Result<BlessOutcome, ProgramError> {
+    let challenge_dir = found.dir();
+    Ok(match expected.stdout {
+        _ if expected.checker.is_some() => {
+            BlessOutcome::Skipped("its output is decided by a checker".to_owned())
+        }
+        Some(stdout) if !BLESSABLE_MATCHERS.contains(&stdout.matcher.as_str()) => {
+            BlessOutcome::Skipped(format!("it uses the '{}' matcher", stdout.matcher))
+        }
+        Some(OutputExpectation {
+            content: StringReference::File { file },
+            ..
+        }) => {
+            let filepath = case.dir(challenge_dir).join(file);
+            outputs.push((filepath.clone(), output));
+            BlessOutcome::WroteFile(filepath)
+        }
+        stdout => {
+            let exact = stdout.is_some_and(|s| s.matcher == "exact");
+            let value = if exact {
+                output
+            } else {
+                trimmed_value(&output)
+            };
+            let path = match (&case.origin().file, found.file()) {
+                (Some(file), _) => challenge_dir.join(file),
+                (None, Some(challenge_file)) => challenge_file.to_owned(),
+                (None, None) => unreachable!("every case of a workspace is in its own file"),
+            };
+            let file = match files.entry(path) {
+                indexmap::map::Entry::Occupied(entry) => entry.into_mut(),
+                indexmap::map::Entry::Vacant(entry) => {
+                    let file = BlessedFile::read(entry.key())?;
+                    entry.insert(file)
+                }
+            };
+            let mut key_path = expectation_path(case);
+            if lookup(&file.document, &key_path).is_some_and(serde_yaml::Value::is_mapping) {
+                key_path.push("value".to_owned());
+            }
+            let key_path = key_path.iter().map(String::as_str).collect::<Vec<_>>();
+            file.source = file
+                .format
+                .set_value(&file.source, &key_path, &value.into())
+                .map_err(|source| BlessError::CouldNotEditConfig {
+                    case: case.name.clone(),
+                    source,
+                })?;
+            file.edited = true;
+            BlessOutcome::UpdatedConfig
+        }
+    })
+}
+
+/// Output as it's compared by the default matcher, keeping a final newline
+/// on multi-line output so it is written as a plain literal block.
+fn trimmed_value(output: &str) -> String {
+    let trimmed = output.trim();
+    if trimmed.contains('\n') {
+        format!("{trimmed}\n")
+    } else {
+        trimmed.to_owned()
+    }
+}
+
+/// Keys leading to a case's expected stdout in the file it was declared in.
+fn expectation_path(case: &ChallengeCase) -> Vec<String> {
+    case.origin()
+        .parent_path
+        .iter()
+        .flat_map(|part| ["parts".to_owned(), part.clone()])
+        .chain(["cases", &case.name, "expected", "stdout"].map(str::to_owned))
+        .collect()
+}
+
+pub(crate) fn lookup<'a>(
+    document: &'a serde_yaml::Value,
+    path: &[String],
+) -> Option<&'a serde_yaml::Value> {
+    path.iter().try_fold(document, |value, key| value.get(key))
+}
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::challenge::process::{
+    isolate_process_group, join_pipe, spawn_pipe_reader, wait_with_timeout,
+};
+use crate::challenge::{ChallengeCommand, ChallengeExecutionError, StringReference};
+
+/// Path of the file holding the case's stdin.
+pub const CHECKER_INPUT_VAR: &str = "CHALLENGE_INPUT";
+/// Path of the file holding the case's actual stdout.
+pub const CHECKER_OUTPUT_VAR: &str = "CHALLENGE_OUTPUT";
+/// Path of the file holding the reference answer, if the case has one.
+pub const CHECKER_EXPECTED_VAR: &str = "CHALLENGE_EXPECTED";
+
+static CHECKER_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Scratch directory for the files handed to a checker, removed on drop.
+struct CheckerDir(PathBuf);
+impl CheckerDir {
+    fn create() -> std::io::Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "challenge_script-{}-{}",
+            std::process::id(),
+            CHECKER_DIR_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        create_dir_all(&dir)?;
+        Ok(Self(dir))
+    }
+
+    /// Resolve a string reference to a file path, writing immediate strings
+    /// into this directory.
+    fn file_for<P: AsRef<Path>>(
+        &self,
+        name: &str,
+        reference: &StringReference,
+        files_dir: P,
+    ) -> std::io::Result<PathBuf> {
+        match reference {
+            StringReference::Immediate(s) => self.write(name, s),
+            // The checker may run elsewhere, so relative paths would be
+            // resolved against the wrong directory.
+            StringReference::File { file } => std::path::absolute(files_dir.as_ref().join(file)),
+        }
+    }
+
+    fn write(&self, name: &str, content: &str) -> std::io::Result<PathBuf> {
+        let path = self.0.join(name);
+        write(&path, content)?;
+        Ok(path)
+    }
+}
+impl Drop for CheckerDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
+
+/// The data a checker decides on.
+pub struct CheckerInput<'a> {
+    pub stdin: Option<&'a StringReference>,
+    pub output: &'a str,
+    pub reference: Option<&'a StringReference>,
+}
+
+/// Run a checker command for a case in `challenge_dir`, reading the files
+/// the case refers to from `files_dir`. The checker accepts the output by
+/// exiting successfully.
+pub fn run_checker<P: AsRef<Path>, Q: AsRef<Path>>(
+    checker: &ChallengeCommand,
+    vars: &HashMap<String, String>,
+    challenge_dir: P,
+    files_dir: Q,
+    input: CheckerInput,
+    timeout: Option<Duration>,
+) -> Result<(), ChallengeExecutionError> {
+    let dir = CheckerDir::create().map_err(ChallengeExecutionError::CouldNotPrepareChecker)?;
+    let prepare = || -> std::io::Result<_> {
+        let input_path = match input.stdin {
+            Some(stdin) => dir.file_for("input.txt", stdin, &files_dir)?,
+            None => dir.write("input.txt", "")?,
+        };
+        let output_path = dir.write("output.txt", input.output)?;
+        let expected_path = input
+            .reference
+            .map(|r| dir.file_for("expected.txt", r, &files_dir))
+            .transpose()?;
+        Ok((input_path, output_path, expected_path))
+    };
+    let (input_path, output_path, expected_path) =
+        prepare().map_err(ChallengeExecutionError::CouldNotPrepareChecker)?;
+
+    let mut cmd = checker.get_command_with(vars)?;
+    cmd.current_dir(&challenge_dir);
+    cmd.env(CHECKER_INPUT_VAR, input_path);
+    cmd.env(CHECKER_OUTPUT_VAR, output_path);
+    match expected_path {
+        Some(path) => cmd.env(CHECKER_EXPECTED_VAR, path),
+        None => cmd.env_remove(CHECKER_EXPECTED_VAR),
+    };
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    isolate_process_group(&mut cmd);
+
+    let started = Instant::now();
+    let mut child = cmd.spawn().map_err(ChallengeExecutionError::SpawnFailed)?;
+    let stdout_reader = spawn_pipe_reader(
+        child
+            .stdout
+            .take()
+            .ok_or(ChallengeExecutionError::ClosedStdout)?,
+    );
+    let stderr_reader = spawn_pipe_reader(
+        child
+            .stderr
+            .take()
+            .ok_or(ChallengeExecutionError::ClosedStderr)?,
+    );
+    let status = wait_with_timeout(&mut child, started, timeout)?;
+    let stdout = join_pipe(stdout_reader).map_err(ChallengeExecutionError::CouldNotReadStdout)?;
+    let stderr = join_pipe(stderr_reader).map_err(ChallengeExecutionError::CouldNotReadStderr)?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(ChallengeExecutionError::CheckerRejected {
+            status,
+            message: format!("{stdout}{stderr}").trim().to_owned(),
+        })
+    }
+}
@@ -1,11 +1,26 @@
 use std::collections::HashMap;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-#[derive(Debug, Default, Deserialize, Clone)]
+use crate::challenge::misc::{StderrMode, Timeout};
+
+#[derive(Debug, Default, Deserialize, JsonSchema, Clone)]
 pub struct CommandConfig {
+    /// Additional arguments to pass when running a command. Appended after
+    /// any higher-level arguments.
     pub arguments: Option<Vec<String>>,
+    /// Environment variables to set when running a command. Overrides
+    /// conflicting higher-level variables.
     pub environment: Option<HashMap<String, String>>,
+    /// Variables to fill in as '{{name}}' in commands, arguments, environment
+    /// values, stdin and file paths. Overrides conflicting higher-level
+    /// variables.
+    pub vars: Option<HashMap<String, String>>,
+    /// Overrides higher-level timeouts.
+    pub timeout: Option<Timeout>,
+    /// Overrides higher-level settings.
+    pub stderr: Option<StderrMode>,
 }
 
 impl CommandConfig {
@@ -21,6 +36,13 @@ impl CommandConfig {
                 (se, None) => se.clone(),
                 (None, oe) => oe.clone(),
             },
+            vars: match (&self.vars, &other.vars) {
+                (Some(sv), Some(ov)) => Some(sv.clone().into_iter().chain(ov.clone()).collect()),
+                (sv, None) => sv.clone(),
+                (None, ov) => ov.clone(),
+            },
+            timeout: other.timeout.or(self.timeout),
+            stderr: other.stderr.or(self.stderr),
         }
     }
 }
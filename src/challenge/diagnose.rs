@@ -0,0 +1,556 @@
+//! Pinpoint why a challenge file failed to parse. Untagged enums only report
+//! that nothing matched, so the document is walked again shape by shape to
+//! find the offending value and why each accepted shape rejected it.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use schemars::{JsonSchema, SchemaGenerator};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::challenge::parsing::{
+    ChallengeCaseData, ChallengeCommandObjectData, ChallengeConfigGroupData,
+    ChallengeConfigPartData, ChallengeExpectationData, IncludeData, MatchedOutputData,
+};
+use crate::challenge::suggest::did_you_mean;
+use crate::challenge::{CaseOrder, ConfigFormat, ExitCodeExpectation, StderrMode, Timeout};
+
+/// A problem in a challenge file, located in its source.
+#[derive(Debug, Clone)]
+pub struct ConfigDiagnostic {
+    pub file: Option<PathBuf>,
+    /// Keys leading to the offending value, e.g. `parts.day3.cases.sample`.
+    pub path: String,
+    /// 1-based line and column of the offending entry, if it could be found.
+    pub location: Option<(usize, usize)>,
+    pub source_line: Option<String>,
+    /// Number of characters to underline from `location`.
+    pub span: usize,
+    pub message: String,
+    /// Why the value didn't fit each of the shapes it may take.
+    pub reasons: Vec<String>,
+}
+impl Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file.display())?;
+        }
+        if let Some((line, column)) = self.location {
+            write!(f, "{line}:{column}:")?;
+        }
+        if self.file.is_some() || self.location.is_some() {
+            write!(f, " ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if !self.path.is_empty() {
+            write!(f, " (at '{}')", self.path)?;
+        }
+
+        let gutter = " ".repeat(self.location.map_or(1, |(line, _)| line.to_string().len()));
+        if let (Some((line, column)), Some(source_line)) = (self.location, &self.source_line) {
+            write!(f, "\n{gutter} |")?;
+            write!(f, "\n{line} | {source_line}")?;
+            write!(
+                f,
+                "\n{gutter} | {}{}",
+                " ".repeat(column - 1),
+                "^".repeat(self.span.max(1))
+            )?;
+        }
+        for reason in &self.reasons {
+            write!(f, "\n{gutter} = {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+impl ConfigDiagnostic {
+    /// A problem at the 1-based line and column `location` of `source`.
+    pub(super) fn located(source: &str, location: Option<(usize, usize)>, message: String) -> Self {
+        Self {
+            location: location.map(|(line, column)| (line, column.max(1))),
+            source_line: location.and_then(|(line, _)| {
+                source
+                    .lines()
+                    .nth(line.saturating_sub(1))
+                    .map(str::to_owned)
+            }),
+            ..Self::unlocated(message)
+        }
+    }
+
+    /// A problem that can't be pinned to a place in the file.
+    pub(super) fn unlocated(message: String) -> Self {
+        Self {
+            file: None,
+            path: String::new(),
+            location: None,
+            source_line: None,
+            span: 1,
+            message,
+            reasons: Vec::new(),
+        }
+    }
+}
+
+/// A problem found by walking a challenge file, before it's been located.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Problem {
+    pub path: Vec<String>,
+    pub message: String,
+    pub reasons: Vec<String>,
+}
+impl Problem {
+    pub fn new(path: &[String], message: &str) -> Self {
+        let mut message = message.to_owned();
+        if let Some(first) = message.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        Self {
+            path: path.to_vec(),
+            message,
+            reasons: Vec::new(),
+        }
+    }
+
+    /// Find the entry this problem is about in `source`.
+    pub fn locate(self, source: &str, format: ConfigFormat) -> ConfigDiagnostic {
+        let keys = self.path.iter().map(String::as_str).collect::<Vec<_>>();
+        let found = format.locate(source, &keys);
+        ConfigDiagnostic {
+            file: None,
+            path: self.path.join("."),
+            location: found.map(|(line, column, _)| (line + 1, column + 1)),
+            source_line: found.and_then(|(line, _, _)| source.lines().nth(line).map(str::to_owned)),
+            span: found.map_or(1, |(_, _, len)| len),
+            message: self.message,
+            reasons: self.reasons,
+        }
+    }
+}
+
+type Check = Result<(), Problem>;
+
+/// Explain `err`, which was returned when reading `document`, parsed from
+/// `source`, as a challenge file.
+pub fn diagnose(
+    source: &str,
+    format: ConfigFormat,
+    document: &Value,
+    err: serde_yaml::Error,
+) -> ConfigDiagnostic {
+    match check_document(document).into_iter().next() {
+        Some(problem) => problem.locate(source, format),
+        None => ConfigDiagnostic::unlocated(err.to_string()),
+    }
+}
+
+/// Every value in `document` that doesn't have the shape a challenge file
+/// expects there, in document order.
+pub(super) fn check_document(document: &Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if !document.is_mapping() {
+        problems.push(Problem::new(&[], "A challenge file must be a mapping"));
+        return problems;
+    }
+    match document.get("name") {
+        Some(name) => report(&mut problems, typed::<String>(name, &["name".to_owned()])),
+        None => problems.push(Problem::new(&[], "Missing field `name`")),
+    }
+    check_node(document, &[], &["name".to_owned()], &mut problems);
+    problems
+}
+
+// Keys each kind of mapping may have, read from the schemas generated for the
+// types they're parsed into so the two can't drift apart.
+static GROUP_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<ChallengeConfigGroupData>);
+static PART_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<ChallengeConfigPartData>);
+static CASE_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<ChallengeCaseData>);
+static EXPECTED_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<ChallengeExpectationData>);
+static MATCHER_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<MatchedOutputData>);
+static INCLUDE_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<IncludeData>);
+static FILE_REFERENCE_KEYS: LazyLock<Vec<String>> = LazyLock::new(keys_of::<FileReference>);
+static COMMAND_OBJECT_KEYS: LazyLock<Vec<String>> =
+    LazyLock::new(keys_of::<ChallengeCommandObjectData>);
+
+fn keys_of<T: JsonSchema>() -> Vec<String> {
+    let schema = SchemaGenerator::default().into_root_schema_for::<T>();
+    schema
+        .get("properties")
+        .and_then(serde_json::Value::as_object)
+        .map(|properties| properties.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// A group with `parts`, a part with `cases`, or an `include` of another
+/// file. `extra_keys` are also allowed, for the top level of the file.
+fn check_node(value: &Value, path: &[String], extra_keys: &[String], problems: &mut Vec<Problem>) {
+    let before = problems.len();
+    let mut node = value.clone();
+    if let Some(mapping) = node.as_mapping_mut() {
+        mapping.retain(|key, _| !extra_keys.contains(&key_name(key)));
+    }
+    if let Some(parts) = value.get("parts") {
+        check_keys(value, path, &[extra_keys, &GROUP_KEYS], problems);
+        check_settings(value, path, &GROUP_KEYS, problems);
+        check_children(
+            parts,
+            &child_path(path, "parts"),
+            check_group_child,
+            problems,
+        );
+        if problems.len() == before {
+            report(problems, typed::<ChallengeConfigGroupData>(&node, path));
+        }
+    } else if let Some(cases) = value.get("cases") {
+        check_keys(value, path, &[extra_keys, &PART_KEYS], problems);
+        check_settings(value, path, &PART_KEYS, problems);
+        check_children(cases, &child_path(path, "cases"), check_case, problems);
+        if problems.len() == before {
+            report(problems, typed::<ChallengeConfigPartData>(&node, path));
+        }
+    } else if let Some(include) = value.get("include") {
+        check_keys(value, path, &[extra_keys, &INCLUDE_KEYS], problems);
+        report(
+            problems,
+            typed::<String>(include, &child_path(path, "include")),
+        );
+        if problems.len() == before {
+            report(problems, typed::<IncludeData>(&node, path));
+        }
+    } else {
+        problems.push(Problem {
+            path: path.to_vec(),
+            message: "Expected a group with `parts`, a part with `cases` or an `include`"
+                .to_owned(),
+            reasons: [
+                reason::<ChallengeConfigGroupData>("a group", value),
+                reason::<ChallengeConfigPartData>("a part", value),
+                reason::<IncludeData>("an include", value),
+            ]
+            .into_iter()
+            .flatten()
+            .collect(),
+        });
+    }
+}
+
+fn check_group_child(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    check_node(value, path, &[], problems);
+}
+
+fn check_children(
+    children: &Value,
+    path: &[String],
+    check: fn(&Value, &[String], &mut Vec<Problem>),
+    problems: &mut Vec<Problem>,
+) {
+    let Some(children) = children.as_mapping() else {
+        report(problems, typed::<HashMap<String, Value>>(children, path));
+        return;
+    };
+    for (name, child) in children {
+        check(child, &child_path(path, &key_name(name)), problems);
+    }
+}
+
+/// Report keys of `value` that aren't in any of `allowed`.
+fn check_keys(value: &Value, path: &[String], allowed: &[&[String]], problems: &mut Vec<Problem>) {
+    let Some(mapping) = value.as_mapping() else {
+        return;
+    };
+    let allowed = allowed.iter().copied().flatten().map(String::as_str);
+    for key in mapping.keys().map(key_name) {
+        if allowed.clone().any(|known| known == key) {
+            continue;
+        }
+        let mut reasons = did_you_mean(&key, allowed.clone())
+            .into_iter()
+            .map(|known| format!("did you mean '{known}'?"))
+            .collect::<Vec<_>>();
+        reasons.push(format!(
+            "expected one of: {}",
+            allowed.clone().collect::<Vec<_>>().join(", ")
+        ));
+        problems.push(Problem {
+            path: child_path(path, &key),
+            message: format!("Unknown key '{key}'"),
+            reasons,
+        });
+    }
+}
+
+/// Settings shared by groups, parts and cases, out of the allowed `keys`.
+fn check_settings(value: &Value, path: &[String], keys: &[String], problems: &mut Vec<Problem>) {
+    for key in keys.iter().map(String::as_str) {
+        let Some(setting) = value.get(key) else {
+            continue;
+        };
+        let path = child_path(path, key);
+        match key {
+            "command" => check_command(setting, &path, problems),
+            "arguments" => report(problems, typed::<Vec<String>>(setting, &path)),
+            "environment" | "vars" => {
+                report(problems, typed::<HashMap<String, String>>(setting, &path))
+            }
+            "timeout" => report(problems, typed::<Timeout>(setting, &path)),
+            "stderr" => report(problems, typed::<StderrMode>(setting, &path)),
+            "parallel" => report(problems, typed::<bool>(setting, &path)),
+            "order" => report(problems, typed::<CaseOrder>(setting, &path)),
+            _ => {}
+        }
+    }
+}
+
+fn check_case(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    let before = problems.len();
+    check_keys(value, path, &[&CASE_KEYS], problems);
+    check_settings(value, path, &CASE_KEYS, problems);
+    if let Some(stdin) = value.get("stdin") {
+        check_string_reference(stdin, &child_path(path, "stdin"), problems);
+    }
+    if let Some(expected) = value.get("expected") {
+        check_expected(expected, &child_path(path, "expected"), problems);
+    }
+    if problems.len() == before {
+        report(problems, typed::<ChallengeCaseData>(value, path));
+    }
+}
+
+fn check_expected(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    let before = problems.len();
+    check_keys(value, path, &[&EXPECTED_KEYS], problems);
+    for stream in ["stdout", "stderr"] {
+        if let Some(output) = value.get(stream) {
+            check_output(output, &child_path(path, stream), problems);
+        }
+    }
+    if let Some(exit_code) = value.get("exit_code") {
+        let path = child_path(path, "exit_code");
+        report(problems, typed::<ExitCodeExpectation>(exit_code, &path));
+    }
+    if let Some(checker) = value.get("checker") {
+        check_command(checker, &child_path(path, "checker"), problems);
+    }
+    if problems.len() == before {
+        report(problems, typed::<ChallengeExpectationData>(value, path));
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(expecting = "a file reference", deny_unknown_fields)]
+struct FileReference {
+    #[allow(dead_code)]
+    file: String,
+}
+
+fn check_string_reference(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    if value.get("file").is_some() {
+        let before = problems.len();
+        check_keys(value, path, &[&FILE_REFERENCE_KEYS], problems);
+        if problems.len() == before {
+            report(problems, typed::<FileReference>(value, path));
+        }
+        return;
+    }
+    report(
+        problems,
+        one_of(
+            value,
+            path,
+            [
+                reason::<String>("text", value),
+                reason::<FileReference>("a file reference", value),
+            ],
+        ),
+    );
+}
+
+fn check_output(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    if value.get("match").is_some() {
+        let before = problems.len();
+        check_keys(value, path, &[&MATCHER_KEYS], problems);
+        if problems.len() == before {
+            report(problems, typed::<MatchedOutputData>(value, path));
+        }
+        return;
+    }
+    if value.get("file").is_some() {
+        check_string_reference(value, path, problems);
+        return;
+    }
+    report(
+        problems,
+        one_of(
+            value,
+            path,
+            [
+                reason::<String>("text", value),
+                reason::<FileReference>("a file reference", value),
+                reason::<MatchedOutputData>("a matcher", value),
+            ],
+        ),
+    );
+}
+
+fn check_command(value: &Value, path: &[String], problems: &mut Vec<Problem>) {
+    if let Some(script) = value.get("script") {
+        let before = problems.len();
+        check_keys(value, path, &[&COMMAND_OBJECT_KEYS], problems);
+        report(
+            problems,
+            one_of(
+                script,
+                &child_path(path, "script"),
+                [
+                    reason::<String>("a command string", script),
+                    reason::<Vec<String>>("a list of arguments", script),
+                ],
+            ),
+        );
+        if problems.len() == before {
+            report(problems, typed::<ChallengeCommandObjectData>(value, path));
+        }
+        return;
+    }
+    report(
+        problems,
+        one_of(
+            value,
+            path,
+            [
+                reason::<String>("a command string", value),
+                reason::<Vec<String>>("a list of arguments", value),
+                reason::<ChallengeCommandObjectData>("a command object", value),
+            ],
+        ),
+    );
+}
+
+fn report(problems: &mut Vec<Problem>, check: Check) {
+    if let Err(problem) = check {
+        problems.push(problem);
+    }
+}
+
+/// Accept `value` if any shape did, or explain why each one didn't.
+fn one_of<const N: usize>(value: &Value, path: &[String], reasons: [Option<String>; N]) -> Check {
+    if reasons.iter().any(Option::is_none) {
+        return Ok(());
+    }
+    Err(Problem {
+        path: path.to_vec(),
+        message: format!("{} doesn't match any accepted shape", describe(value)),
+        reasons: reasons.into_iter().flatten().collect(),
+    })
+}
+
+/// Why `value` can't be read as `shape`, or `None` if it can.
+fn reason<T: DeserializeOwned>(shape: &str, value: &Value) -> Option<String> {
+    serde_yaml::from_value::<T>(value.clone())
+        .err()
+        .map(|err| format!("as {shape}: {err}"))
+}
+
+fn typed<T: DeserializeOwned>(value: &Value, path: &[String]) -> Check {
+    serde_yaml::from_value::<T>(value.clone())
+        .map(|_| ())
+        .map_err(|err| Problem::new(path, &err.to_string()))
+}
+
+pub(super) fn child_path(path: &[String], key: &str) -> Vec<String> {
+    path.iter().cloned().chain([key.to_owned()]).collect()
+}
+
+/// A mapping key as it's written in the file.
+pub(super) fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(name) => name.clone(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim()
+            .to_owned(),
+    }
+}
+
+fn describe(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "An empty value",
+        Value::Bool(_) => "A boolean",
+        Value::Number(_) => "A number",
+        Value::String(_) => "A string",
+        Value::Sequence(_) => "A list",
+        Value::Mapping(_) => "A mapping",
+        Value::Tagged(_) => "A tagged value",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn diagnose_str(source: &str) -> ConfigDiagnostic {
+        let err = crate::challenge::ChallengeConfig::parse_str(source).unwrap_err();
+        match err {
+            crate::challenge::ChallengeParseError::Invalid(diagnostic) => *diagnostic,
+            err => panic!("Unexpected Error: {err:?}"),
+        }
+    }
+
+    #[test]
+    fn test_diagnose_node() {
+        let diagnostic = diagnose_str("name: x\nparts:\n  day1:\n    command: echo\n");
+        assert_eq!(diagnostic.path, "parts.day1");
+        assert_eq!(diagnostic.location, Some((3, 3)));
+        assert_eq!(
+            diagnostic.reasons,
+            [
+                "as a group: missing field `parts`",
+                "as a part: missing field `cases`",
+                "as an include: unknown field `command`, expected `include`"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnose_command() {
+        let diagnostic = diagnose_str("name: x\ncommand:\n  script: 3\ncases: {}\n");
+        assert_eq!(diagnostic.path, "command.script");
+        assert_eq!(diagnostic.location, Some((3, 3)));
+        assert_eq!(diagnostic.reasons.len(), 2);
+    }
+
+    #[test]
+    fn test_keys_from_types() {
+        assert_eq!(
+            *CASE_KEYS,
+            [
+                "arguments",
+                "environment",
+                "expected",
+                "stderr",
+                "stdin",
+                "timeout",
+                "vars"
+            ]
+        );
+        assert!(GROUP_KEYS.iter().any(|key| key == "parts"));
+        assert!(PART_KEYS.iter().any(|key| key == "command"));
+        assert_eq!(
+            *MATCHER_KEYS,
+            ["absolute", "file", "match", "relative", "value"]
+        );
+        assert_eq!(*FILE_REFERENCE_KEYS, ["file"]);
+    }
+
+    #[test]
+    fn test_diagnose_syntax_error() {
+        let diagnostic = diagnose_str("name: x\nparts: [\n");
+        assert_eq!(diagnostic.path, "");
+        assert_eq!(diagnostic.location, Some((3, 1)));
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt::{Display, Write};
+
+use similar::{ChangeTag, TextDiff};
+
+/// Number of unchanged lines shown around each change.
+const CONTEXT_LINES: usize = 3;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// Present in both the expected and actual output.
+    Context,
+    /// Only present in the expected output.
+    Expected,
+    /// Only present in the actual output.
+    Actual,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    /// The line without its line ending.
+    pub content: String,
+    /// Whether the line ended in `\r\n` rather than just `\n`.
+    pub crlf: bool,
+    /// Whether this is the last line of its text and isn't followed by a newline.
+    pub missing_newline: bool,
+}
+
+/// A run of changes with surrounding context. Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub expected_start: usize,
+    pub expected_len: usize,
+    pub actual_start: usize,
+    pub actual_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Line-oriented difference between the expected and actual output of a case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputDiff {
+    pub hunks: Vec<DiffHunk>,
+}
+impl OutputDiff {
+    pub fn new(expected: &str, actual: &str) -> Self {
+        let diff = TextDiff::from_lines(expected, actual);
+        let hunks = diff
+            .grouped_ops(CONTEXT_LINES)
+            .into_iter()
+            .filter_map(|ops| {
+                let first = ops.first()?;
+                let last = ops.last()?;
+                let expected_range = first.old_range().start..last.old_range().end;
+                let actual_range = first.new_range().start..last.new_range().end;
+                let lines = ops
+                    .iter()
+                    .flat_map(|op| diff.iter_changes(op))
+                    .map(|change| {
+                        let raw = change.value();
+                        let content = raw.strip_suffix('\n').unwrap_or(raw);
+                        let stripped = content.strip_suffix('\r');
+                        DiffLine {
+                            kind: match change.tag() {
+                                ChangeTag::Equal => DiffLineKind::Context,
+                                ChangeTag::Delete => DiffLineKind::Expected,
+                                ChangeTag::Insert => DiffLineKind::Actual,
+                            },
+                            content: stripped.unwrap_or(content).to_owned(),
+                            crlf: stripped.is_some(),
+                            missing_newline: change.missing_newline(),
+                        }
+                    })
+                    .collect();
+
+                Some(DiffHunk {
+                    expected_start: expected_range.start + 1,
+                    expected_len: expected_range.len(),
+                    actual_start: actual_range.start + 1,
+                    actual_len: actual_range.len(),
+                    lines,
+                })
+            })
+            .collect();
+
+        Self { hunks }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Render as a unified diff, optionally with ANSI colours. Trailing
+    /// whitespace is made visible as `·` (spaces) and `→` (tabs), and `\r\n`
+    /// line endings as `␍`.
+    pub fn render(&self, color: bool) -> String {
+        let paint = |code: &'static str| if color { code } else { "" };
+        let reset = paint(RESET);
+
+        let mut out = String::new();
+        let _ = writeln!(out, "{}--- expected{reset}", paint(RED));
+        let _ = writeln!(out, "{}+++ actual{reset}", paint(GREEN));
+        for hunk in &self.hunks {
+            let _ = writeln!(
+                out,
+                "{}@@ -{},{} +{},{} @@{reset}",
+                paint(CYAN),
+                hunk.expected_start,
+                hunk.expected_len,
+                hunk.actual_start,
+                hunk.actual_len,
+            );
+            for line in &hunk.lines {
+                let (marker, code) = match line.kind {
+                    DiffLineKind::Context => (' ', ""),
+                    DiffLineKind::Expected => ('-', RED),
+                    DiffLineKind::Actual => ('+', GREEN),
+                };
+                let _ = writeln!(
+                    out,
+                    "{}{marker}{}{}{reset}",
+                    paint(code),
+                    show_trailing_whitespace(&line.content),
+                    if line.crlf { "␍" } else { "" }
+                );
+                if line.missing_newline {
+                    let _ = writeln!(out, "\\ No newline at end of file");
+                }
+            }
+        }
+
+        out
+    }
+}
+impl Display for OutputDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.render(false).trim_end())
+    }
+}
+
+fn show_trailing_whitespace(line: &str) -> String {
+    let content = line.trim_end_matches([' ', '\t']);
+    let trailing = line[content.len()..]
+        .chars()
+        .map(|c| if c == '\t' { '→' } else { '·' });
+    content.chars().chain(trailing).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = OutputDiff::new("a\nb\nc\n", "a\nx\nc\n");
+
+        assert_eq!(
+            diff.render(false),
+            "--- expected\n+++ actual\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_context_is_limited() {
+        let expected = (0..20).map(|i| format!("{i}\n")).collect::<String>();
+        let actual = expected.replace("10\n", "ten\n");
+
+        let diff = OutputDiff::new(&expected, &actual);
+
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].expected_start, 8);
+        assert_eq!(diff.hunks[0].lines.len(), 8);
+    }
+
+    #[test]
+    fn test_diff_markers() {
+        let diff = OutputDiff::new("a\nb\n", "a\nb \t\n").render(false);
+        assert!(diff.contains("+b·→\n"));
+
+        let diff = OutputDiff::new("a\nb\n", "a\nb").render(false);
+        assert!(diff.contains("+b\n\\ No newline at end of file\n"));
+
+        let diff = OutputDiff::new("a\nb\n", "a\r\nb\r\n").render(false);
+        assert!(diff.contains("-a\n-b\n+a␍\n+b␍\n"), "{diff}");
+    }
+
+    #[test]
+    fn test_no_diff() {
+        assert!(OutputDiff::new("same\n", "same\n").is_empty());
+    }
+}
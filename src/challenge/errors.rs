@@ -1,19 +1,116 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::time::Duration;
+
 use serde_yaml::Error as YamlError;
 use thiserror::Error;
 
+use crate::challenge::{ConfigDiagnostic, ExitCodeExpectation, OutputDiff, OutputStream};
+use crate::yaml_edit::YamlEditError;
+
 #[derive(Debug, Error)]
 pub enum StringReferenceError {
     #[error(transparent)]
     FileRead(#[from] std::io::Error),
 }
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TimeoutParseError {
+    #[error("Malformed timeout '{0}'")]
+    Malformed(String),
+    #[error("Unknown unit in timeout '{0}'. Expected one of 'ms', 's', 'm' or 'h'")]
+    UnknownUnit(String),
+    #[error("Timeout '{0}' is out of range")]
+    OutOfRange(String),
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ExitCodeParseError {
+    #[error("Unknown exit code '{0}'. Expected a number, a list of numbers or 'any'")]
+    UnknownKeyword(String),
+    #[error("Unknown signal '{0}'")]
+    UnknownSignal(String),
+}
+
+#[derive(Debug, Error)]
+pub enum MatcherError {
+    #[error("Invalid regular expression: {0}")]
+    InvalidRegex(#[from] regex::Error),
+    #[error("Invalid tolerance '{0}'. Tolerances must be finite and non-negative")]
+    InvalidTolerance(f64),
+}
+
 #[derive(Debug, Error)]
 pub enum ChallengeParseError {
     #[error("Failed to parse YAML file: {0}")]
     Yaml(#[from] YamlError),
+    #[error("{0}")]
+    Invalid(Box<ConfigDiagnostic>),
+    #[error("Could not read challenge file: {0}")]
+    Read(#[from] std::io::Error),
 
     #[error("Could not find command for challenge part '{0}'")]
     NoCommandFound(String),
+    #[error("Expected exactly one of 'value' or 'file' in the output matcher of case '{0}'")]
+    InvalidMatchValue(String),
+
+    #[error("Could not find a challenge file at '{}'", .0.display())]
+    IncludeNotFound(PathBuf),
+    #[error("No challenge files match '{0}'")]
+    IncludeMatchedNothing(String),
+    #[error("Invalid include pattern '{include}': {source}")]
+    InvalidInclude {
+        include: String,
+        source: globset::Error,
+    },
+    #[error("Could not read included file '{}': {}", .0.display(), .1)]
+    CouldNotReadInclude(PathBuf, std::io::Error),
+    #[error("'{}' ends up including itself", .0.display())]
+    IncludeCycle(PathBuf),
+    #[error("No challenge files found under '{}'", .0.display())]
+    EmptyWorkspace(PathBuf),
+    #[error("Could not read workspace directory '{}': {}", .0.display(), .1)]
+    CouldNotReadWorkspace(PathBuf, std::io::Error),
+    #[error("Variable '{0}' is built in, so it can't be set in `vars`")]
+    BuiltinVariable(String),
+}
+
+impl From<Box<ConfigDiagnostic>> for ChallengeParseError {
+    fn from(diagnostic: Box<ConfigDiagnostic>) -> Self {
+        ChallengeParseError::Invalid(diagnostic)
+    }
+}
+
+impl ChallengeParseError {
+    /// Attach the path of the file that failed to parse, unless it's an
+    /// included file that's already known.
+    pub fn in_file<P: AsRef<Path>>(self, path: P) -> Self {
+        match self {
+            ChallengeParseError::Invalid(mut diagnostic) => {
+                diagnostic
+                    .file
+                    .get_or_insert_with(|| path.as_ref().to_owned());
+                ChallengeParseError::Invalid(diagnostic)
+            }
+            err => err,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigEditError {
+    #[error(transparent)]
+    Yaml(#[from] YamlEditError),
+    #[error("Couldn't edit TOML document: {0}")]
+    Toml(#[from] toml_edit::TomlError),
+    #[error("Couldn't format value as TOML: {0}")]
+    TomlFormat(#[from] toml_edit::ser::Error),
+    #[error("Couldn't edit JSON document: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Can't edit '{0}' because it isn't a mapping")]
+    NotAMapping(String),
+    #[error("Can't edit an empty key path")]
+    EmptyPath,
 }
 
 #[derive(Debug, Error)]
@@ -22,6 +119,8 @@ pub enum CommandParseError {
     MalformedString(String),
     #[error("Empty command")]
     EmptyCommand,
+    #[error("Can't quote {0:?} for a shell")]
+    Unquotable(String),
 }
 
 #[derive(Debug, Error)]
@@ -30,31 +129,124 @@ pub enum ChallengeExecutionError {
     BadStringReference(#[from] StringReferenceError),
     #[error(transparent)]
     BadCommand(#[from] CommandParseError),
+    #[error(transparent)]
+    BadMatcher(#[from] MatcherError),
+    #[error("Unknown output matcher '{0}'")]
+    UnknownMatcher(String),
 
     #[error("Couldn't open stdin of child process")]
     ClosedStdin,
     #[error("Couldn't open stdout of child process")]
     ClosedStdout,
+    #[error("Couldn't open stderr of child process")]
+    ClosedStderr,
 
     #[error(transparent)]
     CouldNotWriteStdin(std::io::Error),
     #[error(transparent)]
     CouldNotReadStdout(std::io::Error),
     #[error(transparent)]
+    CouldNotReadStderr(std::io::Error),
+    #[error(transparent)]
     SpawnFailed(std::io::Error),
+    #[error("Couldn't prepare files for the checker: {0}")]
+    CouldNotPrepareChecker(std::io::Error),
     #[error(transparent)]
     ExecutionFailed(#[from] std::io::Error),
+    #[error("Timed out after {elapsed:.2?} (limit {limit:.2?})")]
+    TimedOut { elapsed: Duration, limit: Duration },
+
+    #[error("Wrong output on {stream}:\n{diff}")]
+    UnexpectedOutput {
+        stream: OutputStream,
+        expected: String,
+        actual: String,
+        diff: OutputDiff,
+    },
+    #[error("Checker rejected the output with {status}{}", format_message(.message))]
+    CheckerRejected { status: ExitStatus, message: String },
+    #[error("Unexpected {status}, expected {expected}{}", format_stderr(.stderr))]
+    UnexpectedExitStatus {
+        expected: ExitCodeExpectation,
+        status: ExitStatus,
+        stderr: String,
+    },
+}
+impl ChallengeExecutionError {
+    /// Whether the case ran but didn't behave as expected, as opposed to
+    /// the case not being runnable at all.
+    pub fn is_test_failure(&self) -> bool {
+        matches!(
+            self,
+            Self::TimedOut { .. }
+                | Self::UnexpectedOutput { .. }
+                | Self::CheckerRejected { .. }
+                | Self::UnexpectedExitStatus { .. }
+        )
+    }
+}
+
+fn format_hint(suggestions: &[String], available: &[String]) -> String {
+    let mut hint = String::new();
+    if !suggestions.is_empty() {
+        let quoted = suggestions
+            .iter()
+            .map(|s| format!("'{s}'"))
+            .collect::<Vec<_>>();
+        hint += &format!(". Did you mean {}?", quoted.join(" or "));
+    }
+    if !available.is_empty() {
+        hint += &format!(" (available: {})", available.join(", "));
+    }
+    hint
+}
+
+fn format_stderr(stderr: &str) -> String {
+    match stderr.trim_end() {
+        "" => String::new(),
+        stderr => format!("\nstderr:\n{stderr}"),
+    }
+}
 
-    #[error("Wrong output. Expected '{expected}' but found '{actual}'")]
-    UnexpectedOutput { expected: String, actual: String },
+fn format_message(message: &str) -> String {
+    if message.is_empty() {
+        String::new()
+    } else {
+        format!(": {message}")
+    }
 }
 
 #[derive(Debug, Error)]
 pub enum ChallengeCaseError {
     #[error("Expected another case, but found none")]
     NotEnoughCases,
-    #[error("Could not find case '{case}' in config '{config_name}'")]
-    CaseNotFound { case: String, config_name: String },
+    #[error(
+        "Could not find case '{case}' in config '{config_name}'{}",
+        format_hint(.suggestions, .available)
+    )]
+    CaseNotFound {
+        case: String,
+        config_name: String,
+        /// Names close to `case`.
+        suggestions: Vec<String>,
+        /// Every name in `config_name`, if there are only a few.
+        available: Vec<String>,
+    },
     #[error("Too many cases under '{0}'. Did you mean to run in recursive mode?")]
     TooManyCases(String),
+    #[error("Invalid case selector '{selector}': {source}")]
+    InvalidSelector {
+        selector: String,
+        source: globset::Error,
+    },
+    #[error(
+        "No case matches '{selector}' under '{searched}'{}",
+        format_hint(.suggestions, .available)
+    )]
+    SelectorMatchedNothing {
+        selector: String,
+        searched: String,
+        suggestions: Vec<String>,
+        available: Vec<String>,
+    },
 }
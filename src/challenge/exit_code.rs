@@ -0,0 +1,210 @@
+use std::fmt::Display;
+use std::process::ExitStatus;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::challenge::ExitCodeParseError;
+
+/// The exit status a case's command is expected to finish with.
+#[derive(Debug, Deserialize, JsonSchema, Clone, PartialEq, Eq)]
+#[serde(try_from = "ExitCodeExpectationData")]
+pub enum ExitCodeExpectation {
+    Any,
+    Codes(Vec<i32>),
+    Signal(i32),
+}
+impl Default for ExitCodeExpectation {
+    fn default() -> Self {
+        ExitCodeExpectation::Codes(vec![0])
+    }
+}
+impl ExitCodeExpectation {
+    pub fn matches(&self, status: &ExitStatus) -> bool {
+        match self {
+            ExitCodeExpectation::Any => true,
+            ExitCodeExpectation::Codes(codes) => status.code().is_some_and(|c| codes.contains(&c)),
+            ExitCodeExpectation::Signal(signal) => exit_signal(status) == Some(*signal),
+        }
+    }
+}
+impl Display for ExitCodeExpectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitCodeExpectation::Any => write!(f, "any exit status"),
+            ExitCodeExpectation::Codes(codes) if codes.len() == 1 => {
+                write!(f, "exit code {}", codes[0])
+            }
+            ExitCodeExpectation::Codes(codes) => write!(f, "one of exit codes {codes:?}"),
+            ExitCodeExpectation::Signal(signal) => write!(f, "termination by signal {signal}"),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+    None
+}
+
+#[cfg(unix)]
+fn signal_number(name: &str) -> Option<i32> {
+    let name = name.trim().to_ascii_uppercase();
+    Some(match name.strip_prefix("SIG").unwrap_or(&name) {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "ILL" => libc::SIGILL,
+        "ABRT" => libc::SIGABRT,
+        "FPE" => libc::SIGFPE,
+        "KILL" => libc::SIGKILL,
+        "SEGV" => libc::SIGSEGV,
+        "PIPE" => libc::SIGPIPE,
+        "ALRM" => libc::SIGALRM,
+        "TERM" => libc::SIGTERM,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        _ => return None,
+    })
+}
+#[cfg(not(unix))]
+fn signal_number(_name: &str) -> Option<i32> {
+    None
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum SignalData {
+    Number(i32),
+    Name(String),
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum ExitCodeExpectationData {
+    /// A single allowed exit code
+    Code(i32),
+    /// A list of allowed exit codes
+    Codes(Vec<i32>),
+    /// Accept any exit status
+    Keyword(#[schemars(extend("const" = "any"))] String),
+    Signal {
+        /// Signal the command is expected to be terminated by, as a number or
+        /// a name like 'SIGSEGV'
+        signal: SignalData,
+    },
+}
+impl TryFrom<ExitCodeExpectationData> for ExitCodeExpectation {
+    type Error = ExitCodeParseError;
+
+    fn try_from(value: ExitCodeExpectationData) -> Result<Self, Self::Error> {
+        match value {
+            ExitCodeExpectationData::Code(code) => Ok(ExitCodeExpectation::Codes(vec![code])),
+            ExitCodeExpectationData::Codes(codes) => Ok(ExitCodeExpectation::Codes(codes)),
+            ExitCodeExpectationData::Keyword(keyword) if keyword == "any" => {
+                Ok(ExitCodeExpectation::Any)
+            }
+            ExitCodeExpectationData::Keyword(keyword) => {
+                Err(ExitCodeParseError::UnknownKeyword(keyword))
+            }
+            ExitCodeExpectationData::Signal {
+                signal: SignalData::Number(signal),
+            } => Ok(ExitCodeExpectation::Signal(signal)),
+            ExitCodeExpectationData::Signal {
+                signal: SignalData::Name(name),
+            } => signal_number(&name)
+                .map(ExitCodeExpectation::Signal)
+                .ok_or(ExitCodeParseError::UnknownSignal(name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(source: &str) -> Result<ExitCodeExpectation, serde_yaml::Error> {
+        serde_yaml::from_str(source)
+    }
+
+    #[cfg(unix)]
+    fn exited(code: i32) -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(code << 8)
+    }
+
+    #[cfg(unix)]
+    fn killed(signal: i32) -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(signal)
+    }
+
+    #[test]
+    fn test_parse_codes() {
+        assert_eq!(parse("3").unwrap(), ExitCodeExpectation::Codes(vec![3]));
+        assert_eq!(
+            parse("[0, 1]").unwrap(),
+            ExitCodeExpectation::Codes(vec![0, 1])
+        );
+        assert_eq!(parse("any").unwrap(), ExitCodeExpectation::Any);
+        assert!(parse("some").is_err());
+        assert!(parse("0..3").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_signals() {
+        assert_eq!(
+            parse("signal: 9").unwrap(),
+            ExitCodeExpectation::Signal(libc::SIGKILL)
+        );
+        for name in ["SIGSEGV", "segv", " SigSegv "] {
+            assert_eq!(
+                parse(&format!("signal: '{name}'")).unwrap(),
+                ExitCodeExpectation::Signal(libc::SIGSEGV),
+                "{name}"
+            );
+        }
+        assert!(parse("signal: SIGNOPE").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_matches() {
+        let default = ExitCodeExpectation::default();
+        assert!(default.matches(&exited(0)));
+        assert!(!default.matches(&exited(1)));
+        assert!(!default.matches(&killed(libc::SIGTERM)));
+
+        let codes = ExitCodeExpectation::Codes(vec![1, 2]);
+        assert!(codes.matches(&exited(2)));
+        assert!(!codes.matches(&exited(0)));
+
+        let any = ExitCodeExpectation::Any;
+        assert!(any.matches(&exited(101)));
+        assert!(any.matches(&killed(libc::SIGKILL)));
+
+        let signal = ExitCodeExpectation::Signal(libc::SIGSEGV);
+        assert!(signal.matches(&killed(libc::SIGSEGV)));
+        assert!(!signal.matches(&killed(libc::SIGABRT)));
+        assert!(!signal.matches(&exited(libc::SIGSEGV)));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ExitCodeExpectation::default().to_string(), "exit code 0");
+        assert_eq!(
+            ExitCodeExpectation::Codes(vec![1, 2]).to_string(),
+            "one of exit codes [1, 2]"
+        );
+        assert_eq!(ExitCodeExpectation::Any.to_string(), "any exit status");
+        assert_eq!(
+            ExitCodeExpectation::Signal(9).to_string(),
+            "termination by signal 9"
+        );
+    }
+}
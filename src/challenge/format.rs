@@ -0,0 +1,361 @@
+//! The formats challenge files may be written in. Every format is read into
+//! the same document, so they share one data model, and the same diagnostics
+//! and lints.
+
+use std::fmt::Display;
+use std::ops::Range;
+use std::path::Path;
+
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+use toml_edit::{DocumentMut, ImDocument, Item, Key, Table, TableLike};
+
+use crate::challenge::diagnose::key_name;
+use crate::challenge::{ConfigDiagnostic, ConfigEditError};
+use crate::yaml_edit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConfigFormat {
+    #[default]
+    Yaml,
+    Toml,
+    Json,
+}
+impl Display for ConfigFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ConfigFormat::Yaml => "YAML",
+            ConfigFormat::Toml => "TOML",
+            ConfigFormat::Json => "JSON",
+        })
+    }
+}
+
+impl ConfigFormat {
+    /// The format a file with `path`'s extension is written in, if it's one
+    /// of the known extensions.
+    pub fn from_extension<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "yml" | "yaml" => Some(ConfigFormat::Yaml),
+            "toml" => Some(ConfigFormat::Toml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// The format to read the file at `path` as. Files with other extensions
+    /// are read as YAML.
+    pub fn of_file<P: AsRef<Path>>(path: P) -> Self {
+        Self::from_extension(path).unwrap_or_default()
+    }
+
+    /// Read `source` into a document. Syntax errors are reported with where
+    /// in `source` they are.
+    pub(crate) fn parse(self, source: &str) -> Result<Value, Box<ConfigDiagnostic>> {
+        let (message, location) = match self {
+            ConfigFormat::Yaml => match serde_yaml::from_str(source) {
+                Ok(document) => return Ok(document),
+                Err(err) => {
+                    let location = err.location().map(|l| (l.line(), l.column()));
+                    (err.to_string(), location)
+                }
+            },
+            ConfigFormat::Toml => match toml_edit::de::from_str(source) {
+                Ok(document) => return Ok(document),
+                Err(err) => {
+                    let location = err.span().map(|span| {
+                        let (line, column) = line_column(source, span.start);
+                        (line + 1, column + 1)
+                    });
+                    let message = err.message().trim().lines().collect::<Vec<_>>().join(": ");
+                    (message, location)
+                }
+            },
+            ConfigFormat::Json => match serde_json::from_str(source) {
+                Ok(document) => return Ok(document),
+                Err(err) => (err.to_string(), Some((err.line(), err.column()))),
+            },
+        };
+        Err(Box::new(ConfigDiagnostic::located(
+            source, location, message,
+        )))
+    }
+
+    /// Write `document` out in this format.
+    pub(crate) fn to_string(self, document: &Value) -> Result<String, ConfigEditError> {
+        match self {
+            ConfigFormat::Yaml => {
+                Ok(serde_yaml::to_string(document).map_err(yaml_edit::YamlEditError::from)?)
+            }
+            ConfigFormat::Toml => Ok(toml_edit::ser::to_string_pretty(&without_nulls(
+                &string_keys(document),
+            ))?),
+            ConfigFormat::Json => {
+                let mut json = serde_json::to_string_pretty(&string_keys(document))?;
+                json.push('\n');
+                Ok(json)
+            }
+        }
+    }
+
+    /// Find the entry at `path` in `source`, or the deepest entry along it
+    /// that exists. Returns its 0-based line, the column its key starts at
+    /// and the key's length.
+    pub(crate) fn locate(self, source: &str, path: &[&str]) -> Option<(usize, usize, usize)> {
+        match self {
+            ConfigFormat::Yaml => yaml_edit::locate(source, path),
+            ConfigFormat::Toml => locate_toml(source, path),
+            ConfigFormat::Json => locate_json(source, path),
+        }
+    }
+
+    /// Set the entry at `path` in `source` to `value`, creating any missing
+    /// mappings along the way. YAML and TOML keep the comments and formatting
+    /// of everything else, while JSON is written out again.
+    pub(crate) fn set_value(
+        self,
+        source: &str,
+        path: &[&str],
+        value: &Value,
+    ) -> Result<String, ConfigEditError> {
+        match self {
+            ConfigFormat::Yaml => Ok(yaml_edit::set_value(source, path, value)?),
+            ConfigFormat::Toml => set_toml(source, path, value),
+            ConfigFormat::Json => {
+                let mut document = serde_json::from_str(source)?;
+                set_in_document(&mut document, path, value.clone())?;
+                self.to_string(&document)
+            }
+        }
+    }
+}
+
+/// 0-based line and column of the byte at `offset` in `source`.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    (before.matches('\n').count(), before.len() - line_start)
+}
+
+/// `document` with every mapping key as a string, since TOML and JSON don't
+/// have any other kind of key.
+fn string_keys(document: &Value) -> Value {
+    match document {
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .iter()
+                .map(|(key, value)| (Value::String(key_name(key)), string_keys(value)))
+                .collect(),
+        ),
+        Value::Sequence(values) => Value::Sequence(values.iter().map(string_keys).collect()),
+        other => other.clone(),
+    }
+}
+
+/// `document` without the mapping entries set to null, since TOML has no
+/// null and leaving a key out means the same thing.
+fn without_nulls(document: &Value) -> Value {
+    match document {
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key.clone(), without_nulls(value)))
+                .collect(),
+        ),
+        Value::Sequence(values) => Value::Sequence(values.iter().map(without_nulls).collect()),
+        other => other.clone(),
+    }
+}
+
+fn locate_toml(source: &str, path: &[&str]) -> Option<(usize, usize, usize)> {
+    let document = ImDocument::parse(source).ok()?;
+    let mut table: &dyn TableLike = document.as_table();
+    let mut found = None;
+    for key in path {
+        let Some(span) = table.key(key).and_then(Key::span) else {
+            break;
+        };
+        let (line, column) = line_column(source, span.start);
+        found = Some((line, column, span.len()));
+        match table.get(key).and_then(Item::as_table_like) {
+            Some(inner) => table = inner,
+            None => break,
+        }
+    }
+    found
+}
+
+fn locate_json(source: &str, path: &[&str]) -> Option<(usize, usize, usize)> {
+    let mut object = source.find('{')?;
+    let mut found = None;
+    for key in path {
+        let Some((span, value_start)) = find_json_key(source, object, key) else {
+            break;
+        };
+        let (line, column) = line_column(source, span.start);
+        found = Some((line, column, span.len()));
+        if !source[value_start..].starts_with('{') {
+            break;
+        }
+        object = value_start;
+    }
+    found
+}
+
+/// The span of `key` in the JSON object that opens at `open`, along with
+/// where its value starts. Keys of nested objects are skipped.
+fn find_json_key(source: &str, open: usize, key: &str) -> Option<(Range<usize>, usize)> {
+    let bytes = source.as_bytes();
+    let mut depth = 0;
+    let mut i = open;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return None;
+                }
+            }
+            b'"' => {
+                let end = json_string_end(bytes, i)?;
+                let rest = source[end..].trim_start();
+                if depth == 1
+                    && rest.starts_with(':')
+                    && serde_json::from_str::<String>(&source[i..end]).is_ok_and(|k| k == key)
+                {
+                    let value_start = source.len() - rest[1..].trim_start().len();
+                    return Some((i..end, value_start));
+                }
+                i = end;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Index just past the closing quote of the JSON string starting at `start`.
+fn json_string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i + 1),
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn set_toml(source: &str, path: &[&str], value: &Value) -> Result<String, ConfigEditError> {
+    let (last, parents) = path.split_last().ok_or(ConfigEditError::EmptyPath)?;
+    let mut document = source.parse::<DocumentMut>()?;
+    let mut item = document.as_item_mut();
+    for (depth, key) in parents.iter().enumerate() {
+        item = item
+            .as_table_like_mut()
+            .ok_or_else(|| ConfigEditError::NotAMapping(path[..depth].join(".")))?
+            .entry(key)
+            .or_insert_with(|| {
+                let mut table = Table::new();
+                table.set_implicit(true);
+                Item::Table(table)
+            });
+    }
+    let value = string_keys(value);
+    // Mappings under a `[table]` get a table of their own, written after the
+    // ones already there, rather than one long inline table.
+    let new_item = match item {
+        Item::Table(_) if value.is_mapping() => {
+            Item::Table(toml_edit::ser::to_document(&value)?.into_table())
+        }
+        _ => Item::Value(value.serialize(toml_edit::ser::ValueSerializer::new())?),
+    };
+    item.as_table_like_mut()
+        .ok_or_else(|| ConfigEditError::NotAMapping(parents.join(".")))?
+        .insert(last, new_item);
+    Ok(document.to_string())
+}
+
+fn set_in_document(
+    document: &mut Value,
+    path: &[&str],
+    value: Value,
+) -> Result<(), ConfigEditError> {
+    let (last, parents) = path.split_last().ok_or(ConfigEditError::EmptyPath)?;
+    let mut node = document;
+    for (depth, key) in parents.iter().enumerate() {
+        let Value::Mapping(mapping) = node else {
+            return Err(ConfigEditError::NotAMapping(path[..depth].join(".")));
+        };
+        node = mapping
+            .entry(Value::from(*key))
+            .or_insert_with(|| Value::Mapping(Mapping::new()));
+    }
+    let Value::Mapping(mapping) = node else {
+        return Err(ConfigEditError::NotAMapping(parents.join(".")));
+    };
+    mapping.insert(Value::from(*last), value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_locate_toml() {
+        let source = "name = \"x\"\n\n[parts.day1]\ncommand = \"echo\"\ncases = { sample = { stdin = 3 } }\n";
+        let locate = |path: &[&str]| ConfigFormat::Toml.locate(source, path);
+        assert_eq!(locate(&["parts", "day1"]), Some((2, 7, 4)));
+        assert_eq!(locate(&["parts", "day1", "command"]), Some((3, 0, 7)));
+        assert_eq!(
+            locate(&["parts", "day1", "cases", "sample", "stdin"]),
+            Some((4, 21, 5))
+        );
+        assert_eq!(locate(&["parts", "day2"]), Some((2, 1, 5)));
+    }
+
+    #[test]
+    fn test_locate_json() {
+        let source = "{\n  \"name\": \"x\",\n  \"parts\": {\n    \"a\": { \"name\": 1 },\n    \"name\": {}\n  }\n}\n";
+        let locate = |path: &[&str]| ConfigFormat::Json.locate(source, path);
+        assert_eq!(locate(&["name"]), Some((1, 2, 6)));
+        assert_eq!(locate(&["parts", "name"]), Some((4, 4, 6)));
+        assert_eq!(locate(&["parts", "a", "name"]), Some((3, 11, 6)));
+        assert_eq!(locate(&["parts", "b"]), Some((2, 2, 7)));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let toml = ConfigFormat::Toml
+            .parse("name = \"x\"\ncases = {\n")
+            .unwrap_err();
+        assert_eq!(toml.location.map(|(line, _)| line), Some(2));
+        let json = ConfigFormat::Json
+            .parse("{\n  \"name\": \"x\",\n}\n")
+            .unwrap_err();
+        assert_eq!(json.location, Some((3, 1)));
+        assert_eq!(json.source_line.as_deref(), Some("}"));
+    }
+
+    #[test]
+    fn test_set_toml() {
+        let source = "# Comment\nname = \"x\"\n\n[cases.sample]\nstdin = \"\"\nexpected = { stdout = \"\" }\n";
+        let edited = ConfigFormat::Toml
+            .set_value(
+                source,
+                &["cases", "sample", "expected", "stdout"],
+                &Value::from("42"),
+            )
+            .unwrap();
+        assert_eq!(
+            edited,
+            "# Comment\nname = \"x\"\n\n[cases.sample]\nstdin = \"\"\nexpected = { stdout = \"42\" }\n"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Finding the challenge files that `include` entries refer to, and every
+//! challenge file in a workspace.
+
+use std::fs::read_dir;
+use std::path::{Component, Path, PathBuf};
+
+use globset::{GlobBuilder, GlobMatcher};
+
+use crate::challenge::{ChallengeParseError, CHALLENGE_FILE_NAMES};
+
+const PATTERN_CHARS: [char; 4] = ['*', '?', '[', '{'];
+
+/// Whether `include` is a glob pattern, which may match any number of files.
+pub(super) fn is_pattern(include: &str) -> bool {
+    include.contains(PATTERN_CHARS)
+}
+
+/// The challenge files `include` refers to, relative to `dir`. Directories
+/// stand for the challenge file in them, and each segment of the path may be
+/// a glob pattern like `day*`. Files are returned in order of their paths.
+pub(super) fn find_includes(
+    dir: &Path,
+    include: &str,
+) -> Result<Vec<PathBuf>, ChallengeParseError> {
+    let mut candidates = vec![PathBuf::new()];
+    for component in Path::new(include).components() {
+        let Component::Normal(segment) = component else {
+            candidates.iter_mut().for_each(|c| c.push(component));
+            continue;
+        };
+        let segment = segment.to_string_lossy();
+        if !is_pattern(&segment) {
+            candidates.iter_mut().for_each(|c| c.push(&*segment));
+            continue;
+        }
+        let matcher = glob(&segment).map_err(|source| ChallengeParseError::InvalidInclude {
+            include: include.to_owned(),
+            source,
+        })?;
+        candidates = candidates
+            .into_iter()
+            .flat_map(|candidate| matching_entries(dir, candidate, &matcher))
+            .collect();
+    }
+
+    let found = candidates
+        .iter()
+        .filter_map(|candidate| challenge_file(dir, candidate))
+        .collect::<Vec<_>>();
+    if found.is_empty() {
+        return Err(if is_pattern(include) {
+            ChallengeParseError::IncludeMatchedNothing(include.to_owned())
+        } else {
+            ChallengeParseError::IncludeNotFound(dir.join(include))
+        });
+    }
+    Ok(found)
+}
+
+/// Name of a part included by a pattern: that of the directory it's in for
+/// `challenge.yml` files, or the file's own name otherwise.
+pub(super) fn include_name(file: &Path) -> String {
+    let named = match file.file_name() {
+        Some(name) if CHALLENGE_FILE_NAMES.iter().any(|n| *n == name) => {
+            file.parent().and_then(Path::file_name)
+        }
+        _ => file.file_stem(),
+    };
+    named
+        .unwrap_or(file.as_os_str())
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn glob(pattern: &str) -> Result<GlobMatcher, globset::Error> {
+    Ok(GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()?
+        .compile_matcher())
+}
+
+fn matching_entries(dir: &Path, parent: PathBuf, matcher: &GlobMatcher) -> Vec<PathBuf> {
+    let Ok(entries) = read_dir(dir.join(&parent)) else {
+        return Vec::new();
+    };
+    let mut names = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name())
+        .filter(|name| matcher.is_match(name))
+        .collect::<Vec<_>>();
+    names.sort();
+    names.into_iter().map(|name| parent.join(name)).collect()
+}
+
+/// A directory in a workspace, relative to the workspace directory.
+#[derive(Debug)]
+pub(super) enum WorkspaceEntry {
+    /// A directory with a challenge file, which is the path given here.
+    Challenge(PathBuf),
+    /// A directory whose subdirectories have challenge files.
+    Directory(Vec<(String, WorkspaceEntry)>),
+}
+
+/// Every directory under `workspace_dir` with a challenge file in it, nested
+/// the way the directories are. Hidden directories, and those under a
+/// directory with a challenge file, are skipped.
+pub(super) fn discover(
+    workspace_dir: &Path,
+) -> Result<Vec<(String, WorkspaceEntry)>, ChallengeParseError> {
+    let entries = discover_in(workspace_dir, Path::new(""))?;
+    if entries.is_empty() {
+        return Err(ChallengeParseError::EmptyWorkspace(
+            workspace_dir.to_owned(),
+        ));
+    }
+    Ok(entries)
+}
+
+/// Whether `file`, relative to `workspace_dir`, is one of the challenge files
+/// of that workspace.
+pub(super) fn is_member(workspace_dir: &Path, file: &Path) -> bool {
+    fn contains(entries: &[(String, WorkspaceEntry)], file: &Path) -> bool {
+        entries.iter().any(|(_, entry)| match entry {
+            WorkspaceEntry::Challenge(member) => member == file,
+            WorkspaceEntry::Directory(children) => contains(children, file),
+        })
+    }
+    discover_in(workspace_dir, Path::new("")).is_ok_and(|entries| contains(&entries, file))
+}
+
+fn discover_in(
+    workspace_dir: &Path,
+    dir: &Path,
+) -> Result<Vec<(String, WorkspaceEntry)>, ChallengeParseError> {
+    let full_path = workspace_dir.join(dir);
+    let read_err = |err| ChallengeParseError::CouldNotReadWorkspace(full_path.clone(), err);
+    let mut names = Vec::new();
+    for entry in read_dir(&full_path).map_err(read_err)? {
+        let entry = entry.map_err(read_err)?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type().map_err(read_err)?.is_dir() && !name.starts_with('.') {
+            names.push(name);
+        }
+    }
+    names.sort();
+
+    let mut entries = Vec::new();
+    for name in names {
+        let subdir = dir.join(&name);
+        let file = CHALLENGE_FILE_NAMES
+            .iter()
+            .map(|file_name| subdir.join(file_name))
+            .find(|file| workspace_dir.join(file).is_file());
+        let entry = match file {
+            Some(file) => WorkspaceEntry::Challenge(file),
+            None => {
+                let children = discover_in(workspace_dir, &subdir)?;
+                if children.is_empty() {
+                    continue;
+                }
+                WorkspaceEntry::Directory(children)
+            }
+        };
+        entries.push((name, entry));
+    }
+    Ok(entries)
+}
+
+fn challenge_file(dir: &Path, candidate: &Path) -> Option<PathBuf> {
+    let full_path = dir.join(candidate);
+    if full_path.is_file() {
+        return Some(candidate.to_owned());
+    }
+    CHALLENGE_FILE_NAMES
+        .iter()
+        .map(|name| candidate.join(name))
+        .find(|file| dir.join(file).is_file())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_include_name() {
+        assert_eq!(include_name(Path::new("day01/challenge.yml")), "day01");
+        assert_eq!(
+            include_name(Path::new("days/day02/challenge.yaml")),
+            "day02"
+        );
+        assert_eq!(include_name(Path::new("extra.yml")), "extra");
+    }
+}
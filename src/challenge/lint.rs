@@ -0,0 +1,572 @@
+//! Problems that keep a challenge file from running as intended even though
+//! it has the right shape, like missing files or commands that can't run.
+
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use crate::challenge::diagnose::{check_document, child_path, key_name, Problem};
+use crate::challenge::include::{
+    discover, find_includes, include_name, is_pattern, WorkspaceEntry,
+};
+use crate::challenge::parsing::{ChallengeCommandData, Location, WorkspaceData};
+use crate::challenge::{
+    command_words, ChallengeCommand, ChallengeConfig, ChallengeParseError, ConfigDiagnostic,
+    ConfigFormat, BUILTIN_VARS,
+};
+use crate::template::template_string;
+
+/// Every problem in the challenge file `source`, whose cases run in
+/// `challenge_dir`, in the order they appear in the file. Problems in the
+/// files it includes follow, with their `file` set.
+pub(super) fn check(
+    source: &str,
+    format: ConfigFormat,
+    challenge_dir: &Path,
+) -> Vec<ConfigDiagnostic> {
+    check_file(
+        source,
+        format,
+        challenge_dir,
+        &Location::root(challenge_dir, None),
+        &Scope::default(),
+        &mut Vec::new(),
+    )
+}
+
+/// Every problem in the challenge files under `workspace_dir`, each with its
+/// `file` set, and in the workspace file given in `settings`.
+pub(super) fn check_workspace(
+    settings: Option<&str>,
+    format: ConfigFormat,
+    workspace_dir: &Path,
+) -> Vec<ConfigDiagnostic> {
+    let inherited = match workspace_scope(settings, format) {
+        Ok(inherited) => inherited,
+        Err(err) => return vec![ConfigDiagnostic::unlocated(err.to_string())],
+    };
+    let entries = match discover(workspace_dir) {
+        Ok(entries) => entries,
+        Err(err) => return vec![ConfigDiagnostic::unlocated(err.to_string())],
+    };
+
+    let mut files = Vec::new();
+    workspace_files(entries, &Location::root(workspace_dir, None), &mut files);
+    let mut checked = Vec::new();
+    files
+        .into_iter()
+        .flat_map(|(file, at)| {
+            let file = workspace_dir.join(file);
+            check_included(&file, &at, &inherited, &mut checked)
+        })
+        .collect()
+}
+
+/// Every problem in the challenge file at `file`, relative to
+/// `workspace_dir`, checked with what it inherits from the workspace file
+/// given in `settings`.
+pub(super) fn check_member(
+    settings: Option<&str>,
+    format: ConfigFormat,
+    workspace_dir: &Path,
+    file: &Path,
+) -> Vec<ConfigDiagnostic> {
+    let inherited = match workspace_scope(settings, format) {
+        Ok(inherited) => inherited,
+        Err(err) => return vec![ConfigDiagnostic::unlocated(err.to_string())],
+    };
+    let at = Location::root(workspace_dir, None)
+        .in_challenge(file)
+        .in_file(file.to_owned());
+    check_included(&workspace_dir.join(file), &at, &inherited, &mut Vec::new())
+}
+
+/// What the challenges of a workspace inherit from its workspace file.
+fn workspace_scope(
+    settings: Option<&str>,
+    format: ConfigFormat,
+) -> Result<Scope, ChallengeParseError> {
+    let data = settings
+        .map(|settings| WorkspaceData::parse(settings, format))
+        .transpose()?
+        .unwrap_or_default();
+    Ok(Scope {
+        command: data.command().map(|command| Declared {
+            path: Vec::new(),
+            command: None,
+            template: command.template,
+        }),
+        vars: data.vars(),
+        templates: Vec::new(),
+    })
+}
+
+/// The challenge files of a workspace, along with where they sit in it.
+fn workspace_files(
+    entries: Vec<(String, WorkspaceEntry)>,
+    at: &Location,
+    files: &mut Vec<(PathBuf, Location)>,
+) {
+    for (name, entry) in entries {
+        let entry_at = at.child(&name);
+        match entry {
+            WorkspaceEntry::Challenge(file) => {
+                let file_at = entry_at.in_challenge(&file).in_file(file.clone());
+                files.push((file, file_at));
+            }
+            WorkspaceEntry::Directory(children) => workspace_files(children, &entry_at, files),
+        }
+    }
+}
+
+/// `at` is where the file's root sits in the challenge, and `inherited` what
+/// included files inherit from the including group, like a command their
+/// parts may leave out. `checked` holds the included files that have been
+/// checked already, so each is only checked once.
+fn check_file(
+    source: &str,
+    format: ConfigFormat,
+    challenge_dir: &Path,
+    at: &Location,
+    inherited: &Scope,
+    checked: &mut Vec<PathBuf>,
+) -> Vec<ConfigDiagnostic> {
+    let document = match format.parse(source) {
+        Ok(document) => document,
+        Err(diagnostic) => return vec![*diagnostic],
+    };
+
+    let mut problems = check_document(&document);
+    let mut includes = Vec::new();
+    if document.is_mapping() {
+        let name = document.get("name").and_then(Value::as_str).unwrap_or("");
+        let mut lint = Lint {
+            challenge_dir,
+            problems: &mut problems,
+            includes: &mut includes,
+        };
+        lint.node(&document, &[], name, &at.clone().named(name), inherited);
+    }
+
+    let mut diagnostics = problems
+        .into_iter()
+        .map(|problem| problem.locate(source, format))
+        .collect::<Vec<_>>();
+    if diagnostics.is_empty() {
+        let parsed = if inherited.command.is_some() {
+            ChallengeConfig::validate_str(source, format)
+        } else {
+            ChallengeConfig::parse_str_as(source, format, challenge_dir).map(|_| ())
+        };
+        if let Err(err) = parsed {
+            diagnostics.push(ConfigDiagnostic::unlocated(err.to_string()));
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.location);
+
+    for (file, at, inherited) in includes {
+        diagnostics.extend(check_included(&file, &at, &inherited, checked));
+    }
+    diagnostics
+}
+
+/// Problems in the included challenge file at `file`, unless it's been
+/// checked already.
+fn check_included(
+    file: &Path,
+    at: &Location,
+    inherited: &Scope,
+    checked: &mut Vec<PathBuf>,
+) -> Vec<ConfigDiagnostic> {
+    let Ok(canonical) = file.canonicalize() else {
+        return Vec::new();
+    };
+    if checked.contains(&canonical) {
+        return Vec::new();
+    }
+    checked.push(canonical);
+    let diagnostics = match read_to_string(file) {
+        Ok(source) => {
+            let dir = file.parent().unwrap_or(Path::new(""));
+            check_file(
+                &source,
+                ConfigFormat::of_file(file),
+                dir,
+                at,
+                inherited,
+                checked,
+            )
+        }
+        Err(err) => vec![ConfigDiagnostic::unlocated(err.to_string())],
+    };
+    diagnostics
+        .into_iter()
+        .map(|mut diagnostic| {
+            diagnostic.file.get_or_insert_with(|| file.to_owned());
+            diagnostic
+        })
+        .collect()
+}
+
+/// A command, along with where it was set. `command` is `None` if it's
+/// malformed, which is reported separately, or set in an including file.
+#[derive(Clone)]
+struct Declared {
+    path: Vec<String>,
+    command: Option<ChallengeCommand>,
+    /// Whether placeholders in the command and immediate stdin are filled in.
+    template: bool,
+}
+
+/// What a node inherits from the nodes above it.
+#[derive(Clone, Default)]
+struct Scope {
+    command: Option<Declared>,
+    vars: HashMap<String, String>,
+    /// Arguments and environment values set above, along with where. They're
+    /// filled in separately for each case.
+    templates: Vec<(Vec<String>, String)>,
+}
+impl Scope {
+    /// What an included file inherits. Its cases' commands and arguments are
+    /// checked in the including file.
+    fn for_include(&self) -> Self {
+        Self {
+            command: self.command.as_ref().map(|declared| Declared {
+                path: Vec::new(),
+                command: None,
+                template: declared.template,
+            }),
+            vars: self.vars.clone(),
+            templates: Vec::new(),
+        }
+    }
+}
+
+struct Lint<'a> {
+    /// Directory of the file being checked.
+    challenge_dir: &'a Path,
+    problems: &'a mut Vec<Problem>,
+    /// Paths of the files included by this one, where they sit in the
+    /// challenge and what they inherit.
+    includes: &'a mut Vec<(PathBuf, Location, Scope)>,
+}
+impl Lint<'_> {
+    fn report(&mut self, problem: Problem) {
+        // Commands are checked once per case that inherits them.
+        if !self.problems.contains(&problem) {
+            self.problems.push(problem);
+        }
+    }
+
+    /// `path` leads to `value` in the document, and `at` is where it sits in
+    /// the challenge.
+    fn node(
+        &mut self,
+        value: &Value,
+        path: &[String],
+        name: &str,
+        at: &Location,
+        inherited: &Scope,
+    ) {
+        let mut scope = inherited.clone();
+        if let Some(command) = value.get("command") {
+            let command = parse_command(command);
+            scope.command = Some(Declared {
+                path: child_path(path, "command"),
+                template: command.as_ref().is_some_and(|command| command.template),
+                command,
+            });
+        }
+        self.settings(value, path, &mut scope);
+        if let Some(include) = value.get("include").and_then(Value::as_str) {
+            let include_path = child_path(path, "include");
+            self.include(include, &include_path, at, scope.for_include());
+        }
+
+        if let Some(parts) = value.get("parts").and_then(Value::as_mapping) {
+            let parts_path = child_path(path, "parts");
+            for (part_name, part) in parts {
+                let part_name = key_name(part_name);
+                self.node(
+                    part,
+                    &child_path(&parts_path, &part_name),
+                    &part_name,
+                    &at.child(&part_name),
+                    &scope,
+                );
+            }
+        } else if let Some(cases) = value.get("cases").and_then(Value::as_mapping) {
+            if scope.command.is_none() {
+                let at = if path.is_empty() {
+                    child_path(path, "cases")
+                } else {
+                    path.to_vec()
+                };
+                self.report(Problem {
+                    path: at,
+                    message: format!("Part '{name}' has no command"),
+                    reasons: vec!["set `command` on the part or on a group above it".to_owned()],
+                });
+            }
+            let cases_path = child_path(path, "cases");
+            for (case_name, case) in cases {
+                let case_name = key_name(case_name);
+                let case_path = child_path(&cases_path, &case_name);
+                let vars = at.builtin_vars(name, &case_name);
+                self.case(case, &case_path, &case_name, vars, &scope);
+            }
+        }
+    }
+
+    /// Add the variables, arguments and environment values set on `value` to
+    /// `scope`.
+    fn settings(&mut self, value: &Value, path: &[String], scope: &mut Scope) {
+        if let Some(vars) = value.get("vars").and_then(Value::as_mapping) {
+            for (name, var) in vars {
+                let name = key_name(name);
+                if BUILTIN_VARS.contains(&name.as_str()) {
+                    let err = ChallengeParseError::BuiltinVariable(name.clone());
+                    let vars_path = child_path(path, "vars");
+                    self.report(Problem::new(
+                        &child_path(&vars_path, &name),
+                        &err.to_string(),
+                    ));
+                } else if let Some(var) = var.as_str() {
+                    scope.vars.insert(name, var.to_owned());
+                }
+            }
+        }
+        if let Some(arguments) = value.get("arguments").and_then(Value::as_sequence) {
+            let arguments_path = child_path(path, "arguments");
+            for argument in arguments.iter().filter_map(Value::as_str) {
+                scope
+                    .templates
+                    .push((arguments_path.clone(), argument.to_owned()));
+            }
+        }
+        if let Some(environment) = value.get("environment").and_then(Value::as_mapping) {
+            let environment_path = child_path(path, "environment");
+            for (key, env_value) in environment {
+                if let Some(env_value) = env_value.as_str() {
+                    let env_path = child_path(&environment_path, &key_name(key));
+                    scope.templates.push((env_path, env_value.to_owned()));
+                }
+            }
+        }
+    }
+
+    /// `builtin_vars` are the built-in variables filled in for this case.
+    fn case(
+        &mut self,
+        value: &Value,
+        path: &[String],
+        case_name: &str,
+        builtin_vars: HashMap<String, String>,
+        inherited: &Scope,
+    ) {
+        let mut scope = inherited.clone();
+        self.settings(value, path, &mut scope);
+        let mut vars = scope.vars;
+        vars.extend(builtin_vars);
+
+        let mut templates = Vec::new();
+        if let Some(stdin) = value.get("stdin") {
+            let stdin_path = child_path(path, "stdin");
+            // Immediate stdin is filled in like the command is, so without a
+            // command that fills in placeholders, braces are just data.
+            let templated = scope.command.as_ref().is_some_and(|c| c.template);
+            if let Some(input) = stdin.as_str().filter(|_| templated) {
+                templates.push((stdin_path.clone(), input.to_owned()));
+            }
+            if let Some(file) = self.file_reference(stdin, &stdin_path, &vars) {
+                let input_file = self.challenge_dir.join(file);
+                let input_file = std::path::absolute(&input_file).unwrap_or(input_file);
+                vars.insert(
+                    "input_file".to_owned(),
+                    input_file.to_string_lossy().into_owned(),
+                );
+            }
+        }
+
+        if let Some(expected) = value.get("expected") {
+            let expected_path = child_path(path, "expected");
+            for stream in ["stdout", "stderr"] {
+                if let Some(output) = expected.get(stream) {
+                    let stream_path = child_path(&expected_path, stream);
+                    self.output(output, &stream_path, case_name, &vars);
+                }
+            }
+            if let Some(checker) = expected.get("checker").and_then(parse_command) {
+                let checker_path = child_path(&expected_path, "checker");
+                self.command(&checker, &checker_path, &vars);
+            }
+        }
+        if let Some(Declared {
+            path: command_path,
+            command: Some(command),
+            ..
+        }) = &scope.command
+        {
+            self.command(command, command_path, &vars);
+        }
+        for (path, template) in scope.templates.iter().chain(&templates) {
+            for placeholder in placeholders(template) {
+                self.unknown_placeholder(placeholder, path, &vars);
+            }
+        }
+    }
+
+    /// Whether `command` splits into words, and every placeholder in it is
+    /// filled in for this case.
+    fn command(
+        &mut self,
+        command: &ChallengeCommand,
+        path: &[String],
+        vars: &HashMap<String, String>,
+    ) {
+        match command.get_command_with(vars) {
+            Err(err) => self.report(Problem::new(path, &err.to_string())),
+            Ok(cmd) if command.template => {
+                for word in command_words(&cmd) {
+                    for placeholder in placeholders(&word) {
+                        self.unknown_placeholder(placeholder, path, vars);
+                    }
+                }
+            }
+            Ok(_) => {}
+        }
+    }
+
+    fn unknown_placeholder(
+        &mut self,
+        placeholder: &str,
+        path: &[String],
+        vars: &HashMap<String, String>,
+    ) {
+        let name = &placeholder[2..placeholder.len() - 2];
+        if vars.contains_key(name) {
+            return;
+        }
+        let reason = if name == "input_file" {
+            "input_file is only set for cases that read stdin from a file".to_owned()
+        } else {
+            format!(
+                "set it in `vars`, or use one of the built-in variables: {}",
+                BUILTIN_VARS.join(", ")
+            )
+        };
+        self.report(Problem {
+            path: path.to_vec(),
+            message: format!("Unknown placeholder '{placeholder}'"),
+            reasons: vec![reason],
+        });
+    }
+
+    fn output(
+        &mut self,
+        value: &Value,
+        path: &[String],
+        case_name: &str,
+        vars: &HashMap<String, String>,
+    ) {
+        if value.get("match").is_some()
+            && value.get("value").is_some() == value.get("file").is_some()
+        {
+            let err = ChallengeParseError::InvalidMatchValue(case_name.to_owned());
+            self.report(Problem::new(path, &err.to_string()));
+        }
+        self.file_reference(value, path, vars);
+    }
+
+    /// Included files are checked on their own afterwards, so only whether
+    /// they can be found is checked here.
+    fn include(&mut self, include: &str, path: &[String], at: &Location, inherited: Scope) {
+        let files = match find_includes(self.challenge_dir, include) {
+            Ok(files) => files,
+            Err(err) => return self.report(Problem::new(path, &err.to_string())),
+        };
+        for file in files {
+            let relative = at.origin_dir().join(&file);
+            // Patterns make a group of every match, like they do when parsing.
+            let file_at = if is_pattern(include) {
+                at.child(&include_name(&relative))
+            } else {
+                at.clone()
+            };
+            self.includes.push((
+                self.challenge_dir.join(file),
+                file_at.in_file(relative),
+                inherited.clone(),
+            ));
+        }
+    }
+
+    /// Check that the file `value` refers to exists, once `vars` are filled
+    /// in. Returns its path, if it has one.
+    fn file_reference(
+        &mut self,
+        value: &Value,
+        path: &[String],
+        vars: &HashMap<String, String>,
+    ) -> Option<String> {
+        let file = value.get("file").and_then(Value::as_str)?;
+        let file_path = child_path(path, "file");
+        for placeholder in placeholders(file) {
+            self.unknown_placeholder(placeholder, &file_path, vars);
+        }
+        let file = template_string(file, vars);
+        let full_path = self.challenge_dir.join(&file);
+        if !full_path.is_file() {
+            self.report(Problem {
+                path: file_path,
+                message: format!("File '{file}' does not exist"),
+                reasons: vec![format!("looked for {}", full_path.display())],
+            });
+        }
+        Some(file)
+    }
+}
+
+fn parse_command(value: &Value) -> Option<ChallengeCommand> {
+    serde_yaml::from_value::<ChallengeCommandData>(value.clone())
+        .ok()
+        .map(Into::into)
+}
+
+/// Every `{{...}}` left in `word`.
+fn placeholders(word: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = word;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start..];
+        let Some(len) = rest[2..].find("}}") else {
+            break;
+        };
+        // Like when filling them in, a key may start further in.
+        if rest[2..2 + len].contains('{') {
+            rest = &rest[1..];
+            continue;
+        }
+        found.push(&rest[..len + 4]);
+        rest = &rest[len + 4..];
+    }
+    found
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_placeholders() {
+        assert_eq!(placeholders("{{case}}.txt"), ["{{case}}"]);
+        assert_eq!(placeholders("{{a}}-{{ b }}"), ["{{a}}", "{{ b }}"]);
+        assert_eq!(placeholders("{{{a}}}"), ["{{a}}"]);
+        assert_eq!(placeholders("{{ {{a}}"), ["{{a}}"]);
+        assert!(placeholders("{{unclosed").is_empty());
+        assert!(placeholders("plain").is_empty());
+    }
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use regex::Regex;
+
+use crate::challenge::MatcherError;
+
+/// Default absolute tolerance of the `float` matcher when neither tolerance is given.
+const DEFAULT_ABSOLUTE_TOLERANCE: f64 = 1e-6;
+
+/// Extra settings from an expectation's object form, passed to its matcher.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MatchOptions {
+    pub absolute: Option<f64>,
+    pub relative: Option<f64>,
+}
+
+/// Compares the output of a case against its expected content.
+pub trait Matcher: Send + Sync {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        options: &MatchOptions,
+    ) -> Result<bool, MatcherError>;
+
+    /// Whether leading and trailing whitespace affects the result. Diffs of
+    /// mismatched output are only computed on trimmed text if this is false.
+    fn is_whitespace_sensitive(&self) -> bool {
+        false
+    }
+}
+
+/// Byte-for-byte equality.
+pub struct ExactMatcher;
+impl Matcher for ExactMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        _: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        Ok(expected == actual)
+    }
+
+    fn is_whitespace_sensitive(&self) -> bool {
+        true
+    }
+}
+
+/// Equality after trimming leading and trailing whitespace.
+pub struct TrimmedMatcher;
+impl Matcher for TrimmedMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        _: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        Ok(expected.trim() == actual.trim())
+    }
+}
+
+/// The expected content is a regular expression searched for in the trimmed output.
+pub struct RegexMatcher;
+impl Matcher for RegexMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        _: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        Ok(Regex::new(expected.trim())?.is_match(actual.trim()))
+    }
+}
+
+/// The output contains the trimmed expected content.
+pub struct ContainsMatcher;
+impl Matcher for ContainsMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        _: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        Ok(actual.contains(expected.trim()))
+    }
+}
+
+/// The output has the same lines as the expected content, in any order.
+/// Trailing whitespace on each line and surrounding blank lines are ignored.
+pub struct LinesUnorderedMatcher;
+impl Matcher for LinesUnorderedMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        _: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        fn sorted_lines(s: &str) -> Vec<&str> {
+            let mut lines = s.trim().lines().map(str::trim_end).collect::<Vec<_>>();
+            lines.sort_unstable();
+            lines
+        }
+        Ok(sorted_lines(expected) == sorted_lines(actual))
+    }
+}
+
+/// Compares whitespace-separated tokens, allowing numeric tokens to differ by
+/// an absolute or relative tolerance. Non-numeric tokens must match exactly.
+pub struct FloatMatcher;
+impl Matcher for FloatMatcher {
+    fn matches(
+        &self,
+        expected: &str,
+        actual: &str,
+        options: &MatchOptions,
+    ) -> Result<bool, MatcherError> {
+        let absolute = match (options.absolute, options.relative) {
+            (None, None) => Some(DEFAULT_ABSOLUTE_TOLERANCE),
+            (absolute, _) => absolute,
+        };
+        for tol in absolute.iter().chain(options.relative.iter()) {
+            if !(tol.is_finite() && *tol >= 0.0) {
+                return Err(MatcherError::InvalidTolerance(*tol));
+            }
+        }
+
+        let mut expected_tokens = expected.split_whitespace();
+        let mut actual_tokens = actual.split_whitespace();
+        loop {
+            let (e, a) = match (expected_tokens.next(), actual_tokens.next()) {
+                (None, None) => return Ok(true),
+                (Some(e), Some(a)) => (e, a),
+                _ => return Ok(false),
+            };
+            let (Ok(e_num), Ok(a_num)) = (e.parse::<f64>(), a.parse::<f64>()) else {
+                if e != a {
+                    return Ok(false);
+                }
+                continue;
+            };
+
+            let diff = (e_num - a_num).abs();
+            let within_absolute = absolute.is_some_and(|tol| diff <= tol);
+            let within_relative = options
+                .relative
+                .is_some_and(|tol| diff <= tol * e_num.abs().max(a_num.abs()));
+            if !(e_num == a_num || within_absolute || within_relative) {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+/// Matchers available to expectations, looked up by the name given in `match`.
+#[derive(Clone)]
+pub struct MatcherRegistry {
+    matchers: HashMap<String, Arc<dyn Matcher>>,
+}
+impl MatcherRegistry {
+    /// A registry without any matchers, not even the built-in ones.
+    pub fn empty() -> Self {
+        Self {
+            matchers: HashMap::new(),
+        }
+    }
+
+    /// Add a matcher, replacing any existing matcher with the same name.
+    pub fn register<M: Matcher + 'static>(&mut self, name: &str, matcher: M) -> &mut Self {
+        self.matchers.insert(name.to_owned(), Arc::new(matcher));
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Matcher> {
+        self.matchers.get(name).map(Arc::as_ref)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.matchers.keys().map(String::as_str)
+    }
+}
+impl Default for MatcherRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        registry
+            .register("exact", ExactMatcher)
+            .register("trimmed", TrimmedMatcher)
+            .register("regex", RegexMatcher)
+            .register("contains", ContainsMatcher)
+            .register("lines-unordered", LinesUnorderedMatcher)
+            .register("float", FloatMatcher);
+        registry
+    }
+}
+impl std::fmt::Debug for MatcherRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.matchers.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check<M: Matcher>(matcher: M, expected: &str, actual: &str) -> bool {
+        matcher
+            .matches(expected, actual, &MatchOptions::default())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_exact_matcher() {
+        assert!(check(ExactMatcher, "a\nb\n", "a\nb\n"));
+        assert!(!check(ExactMatcher, "a\nb\n", "a\nb"));
+        assert!(!check(ExactMatcher, "a", "a "));
+    }
+
+    #[test]
+    fn test_trimmed_matcher() {
+        assert!(check(TrimmedMatcher, "a\nb\n", "  a\nb"));
+        assert!(!check(TrimmedMatcher, "a\nb", "a\n b"));
+    }
+
+    #[test]
+    fn test_regex_matcher() {
+        assert!(check(RegexMatcher, r"^\d+$", "1234\n"));
+        assert!(check(RegexMatcher, "answer: [a-z]+", "the answer: foo"));
+        assert!(!check(RegexMatcher, r"^\d+$", "12a4\n"));
+    }
+
+    #[test]
+    fn test_bad_regex() {
+        let res = RegexMatcher.matches("(unclosed", "anything", &MatchOptions::default());
+
+        assert!(matches!(res, Err(MatcherError::InvalidRegex(_))));
+    }
+
+    #[test]
+    fn test_contains_matcher() {
+        assert!(check(ContainsMatcher, "needle\n", "hay needle stack"));
+        assert!(!check(ContainsMatcher, "needle", "haystack"));
+    }
+
+    #[test]
+    fn test_lines_unordered_matcher() {
+        assert!(check(LinesUnorderedMatcher, "a\nb\nc\n", "c\na  \nb"));
+        assert!(!check(LinesUnorderedMatcher, "a\nb\nb", "a\na\nb"));
+        assert!(!check(LinesUnorderedMatcher, "a\nb", "a\nb\nc"));
+    }
+
+    #[test]
+    fn test_float_matcher_default_tolerance() {
+        assert!(check(FloatMatcher, "3.1415926", "3.14159265\n"));
+        assert!(check(
+            FloatMatcher,
+            "x = 1.0 y = 2",
+            "x = 1.0000001 y = 2.0"
+        ));
+        assert!(!check(FloatMatcher, "3.14", "3.15"));
+        assert!(!check(FloatMatcher, "x = 1", "y = 1"));
+        assert!(!check(FloatMatcher, "1 2", "1"));
+    }
+
+    #[test]
+    fn test_float_matcher_absolute_tolerance() {
+        let options = MatchOptions {
+            absolute: Some(0.1),
+            relative: None,
+        };
+
+        assert!(FloatMatcher.matches("3.14", "3.2", &options).unwrap());
+        assert!(!FloatMatcher.matches("3.14", "3.3", &options).unwrap());
+    }
+
+    #[test]
+    fn test_float_matcher_relative_tolerance() {
+        let options = MatchOptions {
+            absolute: None,
+            relative: Some(0.01),
+        };
+
+        assert!(FloatMatcher.matches("1000", "1009", &options).unwrap());
+        assert!(!FloatMatcher.matches("1000", "1011", &options).unwrap());
+        assert!(!FloatMatcher.matches("0.001", "0.002", &options).unwrap());
+    }
+
+    #[test]
+    fn test_float_matcher_bad_tolerance() {
+        let options = MatchOptions {
+            absolute: Some(-1.0),
+            relative: None,
+        };
+
+        let res = FloatMatcher.matches("1", "1", &options);
+
+        assert!(matches!(res, Err(MatcherError::InvalidTolerance(_))));
+    }
+
+    #[test]
+    fn test_registry_custom_matcher() {
+        struct CaseInsensitive;
+        impl Matcher for CaseInsensitive {
+            fn matches(
+                &self,
+                expected: &str,
+                actual: &str,
+                _: &MatchOptions,
+            ) -> Result<bool, MatcherError> {
+                Ok(expected.trim().eq_ignore_ascii_case(actual.trim()))
+            }
+        }
+
+        let mut registry = MatcherRegistry::default();
+        registry.register("case-insensitive", CaseInsensitive);
+        let matcher = registry.get("case-insensitive").unwrap();
+
+        assert!(matcher
+            .matches("HELLO", "hello", &MatchOptions::default())
+            .unwrap());
+        assert!(registry.get("trimmed").is_some());
+        assert!(registry.get("nonexistent").is_none());
+    }
+}
@@ -1,14 +1,24 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
 use std::{fs::read_to_string, path::Path};
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::challenge::StringReferenceError;
+use crate::challenge::{StringReferenceError, TimeoutParseError};
+use crate::template::template_string;
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(untagged)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(untagged, deny_unknown_fields)]
 pub enum StringReference {
     Immediate(String),
-    File { file: String },
+    File {
+        /// Path of the file to load data from (relative to this challenge file)
+        file: String,
+    },
 }
 impl StringReference {
     pub fn into_string<P: AsRef<Path>>(
@@ -23,6 +33,153 @@ impl StringReference {
             }
         })
     }
+
+    /// This reference with `vars` filled in. Immediate strings are only
+    /// templated if `immediate` is set, while file paths always are.
+    pub(crate) fn templated(self, vars: &HashMap<String, String>, immediate: bool) -> Self {
+        match self {
+            StringReference::Immediate(s) if immediate => {
+                StringReference::Immediate(template_string(&s, vars))
+            }
+            StringReference::Immediate(s) => StringReference::Immediate(s),
+            StringReference::File { file } => StringReference::File {
+                file: template_string(&file, vars),
+            },
+        }
+    }
+}
+
+/// Maximum time a case may run, written as a number with a unit suffix
+/// (`500ms`, `1.5s`, `2m`, `1h`). A bare number is read as seconds.
+#[derive(Debug, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "TimeoutData")]
+pub struct Timeout(pub Duration);
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum TimeoutData {
+    Seconds(#[schemars(range(min = 0.0))] f64),
+    Text(#[schemars(regex(pattern = r"^\s*[0-9.]+\s*(ms|s|m|h)?\s*$"))] String),
+}
+impl TryFrom<TimeoutData> for Timeout {
+    type Error = TimeoutParseError;
+
+    fn try_from(value: TimeoutData) -> Result<Self, Self::Error> {
+        match value {
+            TimeoutData::Seconds(secs) => Duration::try_from_secs_f64(secs)
+                .map(Timeout)
+                .map_err(|_| TimeoutParseError::OutOfRange(secs.to_string())),
+            TimeoutData::Text(s) => s.parse(),
+        }
+    }
+}
+impl FromStr for Timeout {
+    type Err = TimeoutParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        let unit_start = trimmed
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(trimmed.len());
+        let (value, unit) = trimmed.split_at(unit_start);
+        let value: f64 = value
+            .parse()
+            .map_err(|_| TimeoutParseError::Malformed(s.to_owned()))?;
+        let multiplier = match unit.trim() {
+            "ms" => 0.001,
+            "" | "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return Err(TimeoutParseError::UnknownUnit(s.to_owned())),
+        };
+
+        Duration::try_from_secs_f64(value * multiplier)
+            .map(Timeout)
+            .map_err(|_| TimeoutParseError::OutOfRange(s.to_owned()))
+    }
+}
+impl From<Timeout> for Duration {
+    fn from(value: Timeout) -> Self {
+        value.0
+    }
+}
+
+/// How a case's stderr is shown while it runs. It is captured in every mode.
+#[derive(Debug, Default, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StderrMode {
+    /// Pass stderr through to the terminal as it is written.
+    #[default]
+    Live,
+    /// Don't show stderr, except in the failure message of a case that exits
+    /// with an unexpected status.
+    Capture,
+    /// Show stderr only if the case fails.
+    OnFailure,
+}
+
+/// The order parts and cases are run in.
+#[derive(Debug, Default, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaseOrder {
+    /// The order they are written in.
+    #[default]
+    Declared,
+    /// By name, comparing runs of digits by their numeric value.
+    Natural,
+}
+impl CaseOrder {
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            CaseOrder::Declared => Ordering::Equal,
+            CaseOrder::Natural => natural_cmp(a, b),
+        }
+    }
+}
+
+/// Compare strings so that `day2` comes before `day10`.
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(ca), Some(cb)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        let ordering = if ca.is_ascii_digit() && cb.is_ascii_digit() {
+            let (da, rest_a) = split_digits(a);
+            let (db, rest_b) = split_digits(b);
+            (a, b) = (rest_a, rest_b);
+            let (ta, tb) = (da.trim_start_matches('0'), db.trim_start_matches('0'));
+            ta.len()
+                .cmp(&tb.len())
+                .then_with(|| ta.cmp(tb))
+                .then_with(|| da.len().cmp(&db.len()))
+        } else {
+            (a, b) = (&a[ca.len_utf8()..], &b[cb.len_utf8()..]);
+            ca.cmp(&cb)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s.split_at(end)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+impl Display for OutputStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputStream::Stdout => write!(f, "stdout"),
+            OutputStream::Stderr => write!(f, "stderr"),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -40,4 +197,32 @@ mod test {
 
         assert!(str_res.is_err());
     }
+
+    #[test]
+    fn test_timeout_units() {
+        assert_eq!("500ms".parse(), Ok(Timeout(Duration::from_millis(500))));
+        assert_eq!("1.5s".parse(), Ok(Timeout(Duration::from_millis(1500))));
+        assert_eq!("2m".parse(), Ok(Timeout(Duration::from_secs(120))));
+        assert_eq!("1h".parse(), Ok(Timeout(Duration::from_secs(3600))));
+        assert_eq!("3".parse(), Ok(Timeout(Duration::from_secs(3))));
+    }
+
+    #[test]
+    fn test_bad_timeout() {
+        assert!("fast".parse::<Timeout>().is_err());
+        assert!("10 parsecs".parse::<Timeout>().is_err());
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let mut names = vec![
+            "day10", "day2", "day1", "day01b", "part", "day02", "10", "9",
+        ];
+        names.sort_by(|a, b| CaseOrder::Natural.compare(a, b));
+
+        assert_eq!(
+            names,
+            ["9", "10", "day1", "day01b", "day2", "day02", "day10", "part"]
+        );
+    }
 }
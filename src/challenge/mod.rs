@@ -1,34 +1,146 @@
 use std::collections::{HashMap, VecDeque};
 use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
 
+mod checker;
 mod command;
+mod diagnose;
+mod diff;
 mod errors;
+mod exit_code;
+mod format;
+mod include;
+mod lint;
+mod matcher;
 mod misc;
+mod outcome;
 mod parsing;
+mod process;
+mod schema;
+mod selector;
+mod suggest;
 
+pub use crate::challenge::checker::{CHECKER_EXPECTED_VAR, CHECKER_INPUT_VAR, CHECKER_OUTPUT_VAR};
 pub use crate::challenge::command::CommandConfig;
-use crate::challenge::parsing::ChallengeConfigData;
+use crate::challenge::parsing::{ChallengeConfigData, WorkspaceData};
 use crate::template::template_string;
 
+use self::checker::{run_checker, CheckerInput};
+pub use self::diagnose::ConfigDiagnostic;
+pub use self::diff::{DiffHunk, DiffLine, DiffLineKind, OutputDiff};
 pub use self::errors::{
     ChallengeCaseError, ChallengeExecutionError, ChallengeParseError, CommandParseError,
-    StringReferenceError,
+    ConfigEditError, ExitCodeParseError, MatcherError, StringReferenceError, TimeoutParseError,
 };
-pub use self::misc::StringReference;
+pub use self::exit_code::ExitCodeExpectation;
+pub use self::format::ConfigFormat;
+pub use self::matcher::{
+    ContainsMatcher, ExactMatcher, FloatMatcher, LinesUnorderedMatcher, MatchOptions, Matcher,
+    MatcherRegistry, RegexMatcher, TrimmedMatcher,
+};
+pub use self::misc::{CaseOrder, OutputStream, StderrMode, StringReference, Timeout};
+pub use self::outcome::{CaseOutcome, CaseStatus, RunReport};
+pub use self::process::StderrSink;
+use self::process::{
+    isolate_process_group, join_pipe, spawn_pipe_reader, spawn_stdin_writer, spawn_tee_reader,
+    wait_with_timeout,
+};
+pub use self::schema::challenge_schema;
+use self::selector::{is_selector_syntax, select_cases};
+use self::suggest::{did_you_mean, listable};
+
+/// Name of the matcher used when an expectation doesn't specify one.
+pub const DEFAULT_MATCHER: &str = "trimmed";
+/// Variables every case fills in: the names of its part and itself, its full
+/// path, the directory of the file it's declared in, the challenge's name and,
+/// when stdin is read from a file, that file's path.
+pub const BUILTIN_VARS: [&str; 6] = [
+    "part",
+    "case",
+    "path",
+    "challenge_dir",
+    "challenge_name",
+    "input_file",
+];
+/// Names a challenge file may have, in the order they're looked for.
+pub const CHALLENGE_FILE_NAMES: [&str; 4] = [
+    "challenge.yml",
+    "challenge.yaml",
+    "challenge.toml",
+    "challenge.json",
+];
+/// Names of the file that marks a directory as a workspace of challenges, in
+/// the order they're looked for.
+pub const WORKSPACE_FILE_NAMES: [&str; 4] = [
+    "challenge-workspace.yml",
+    "challenge-workspace.yaml",
+    "challenge-workspace.toml",
+    "challenge-workspace.json",
+];
 
 #[derive(Debug, Clone)]
+pub struct OutputExpectation {
+    pub content: StringReference,
+    /// Name of the matcher in the [`MatcherRegistry`] to compare with.
+    pub matcher: String,
+    pub options: MatchOptions,
+}
+
+#[derive(Debug, Default, Clone)]
 pub struct ChallengeExpectation {
-    pub stdout: StringReference,
+    pub stdout: Option<OutputExpectation>,
+    pub stderr: Option<OutputExpectation>,
+    pub exit_code: ExitCodeExpectation,
+    /// Command that decides whether stdout is correct. When set, `stdout` is
+    /// handed to the checker as a reference answer instead of being compared.
+    pub checker: Option<ChallengeCommand>,
+}
+
+/// Everything a case's command produced.
+#[derive(Debug, Clone)]
+pub struct CaseOutput {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    /// Time from spawning the command until it exited.
+    pub duration: Duration,
+}
+
+/// The file a case was declared in, which may have been included.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CaseOrigin {
+    /// Path of the included file, relative to the challenge directory, or
+    /// `None` for the challenge file itself.
+    pub file: Option<PathBuf>,
+    /// Names of the parts leading to the case from the root of that file.
+    pub parent_path: Vec<String>,
+    /// Directory of the challenge the case belongs to, relative to the
+    /// challenge directory. Only set for the challenges of a workspace.
+    pub challenge_dir: PathBuf,
+}
+impl CaseOrigin {
+    /// Directory of the file, relative to the challenge directory.
+    pub fn dir(&self) -> &Path {
+        self.file
+            .as_deref()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""))
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct ChallengeCase {
     pub name: String,
     parent_name: String,
+    parent_path: Vec<String>,
+    origin: CaseOrigin,
+    serial_group: Option<Vec<String>>,
+    stderr_sink: Option<StderrSink>,
     config: CommandConfig,
+    /// Every variable filled in for this case, built-in ones included.
+    vars: HashMap<String, String>,
     stdin: Option<StringReference>,
     expected: Option<ChallengeExpectation>,
 }
@@ -37,14 +149,74 @@ impl ChallengeCase {
         self,
         challenge_dir: P,
         command: &ChallengeCommand,
-    ) -> Result<(), ChallengeExecutionError> {
-        let mut cmd = command.get_command(&self.parent_name, &self.name)?;
-        if let Some(args) = self.config.arguments {
+    ) -> CaseOutcome {
+        self.execute_with(challenge_dir, command, &MatcherRegistry::default())
+    }
+
+    /// Execute this case, comparing its output using the matchers in `matchers`.
+    pub fn execute_with<P: AsRef<Path>>(
+        self,
+        challenge_dir: P,
+        command: &ChallengeCommand,
+        matchers: &MatcherRegistry,
+    ) -> CaseOutcome {
+        let started = Instant::now();
+        let (output, res) = match self.run(&challenge_dir, command) {
+            Ok(output) => {
+                let res = self.check_expectations(
+                    &challenge_dir,
+                    matchers,
+                    output.status,
+                    &output.stdout,
+                    &output.stderr,
+                );
+                (Some(output), res)
+            }
+            Err(err) => (None, Err(err)),
+        };
+
+        let (stdout, stderr, exit_status, duration) = match output {
+            Some(output) => (
+                output.stdout,
+                output.stderr,
+                Some(output.status),
+                output.duration,
+            ),
+            None => (String::new(), String::new(), None, started.elapsed()),
+        };
+        let stderr_streamed = self.live_stderr_sink().is_some();
+        CaseOutcome {
+            parent_path: self.parent_path,
+            name: self.name,
+            stdout,
+            stderr,
+            exit_status,
+            duration,
+            stderr_mode: self.config.stderr.unwrap_or_default(),
+            stderr_streamed,
+            status: match res {
+                Ok(()) => CaseStatus::Passed,
+                Err(err) => CaseStatus::Failed(err),
+            },
+        }
+    }
+
+    /// Run this case's command and collect its output, without checking it
+    /// against any expectations.
+    pub fn run<P: AsRef<Path>>(
+        &self,
+        challenge_dir: P,
+        command: &ChallengeCommand,
+    ) -> Result<CaseOutput, ChallengeExecutionError> {
+        let dir = self.dir(&challenge_dir);
+        let mut cmd = command.get_command_with(&self.vars)?;
+        if let Some(args) = &self.config.arguments {
             cmd.args(args);
         }
-        cmd.current_dir(&challenge_dir);
+        cmd.current_dir(self.run_dir(&challenge_dir));
         cmd.stdout(Stdio::piped());
-        cmd.stderr(Stdio::inherit());
+        cmd.stderr(Stdio::piped());
+        isolate_process_group(&mut cmd);
 
         if let Some(env_vars) = &self.config.environment {
             for (key, value) in env_vars {
@@ -52,47 +224,256 @@ impl ChallengeCase {
             }
         }
 
-        let mut child = match &self.stdin {
+        let stdin_input = match &self.stdin {
             Some(StringReference::Immediate(s)) => {
                 cmd.stdin(Stdio::piped());
-                let child = cmd.spawn().map_err(ChallengeExecutionError::SpawnFailed)?;
-                let mut child_stdin = child
-                    .stdin
-                    .as_ref()
-                    .ok_or(ChallengeExecutionError::ClosedStdin)?;
-                child_stdin
-                    .write_all(s.as_bytes())
-                    .map_err(ChallengeExecutionError::CouldNotWriteStdin)?;
-                child
+                Some(s.clone())
             }
             Some(StringReference::File { file }) => {
-                let filepath = challenge_dir.as_ref().join(file);
+                let filepath = dir.join(file);
                 cmd.stdin(File::open(filepath).map_err(StringReferenceError::FileRead)?);
-                cmd.spawn().map_err(ChallengeExecutionError::SpawnFailed)?
+                None
+            }
+            None => {
+                // Cases running at the same time would share the terminal.
+                cmd.stdin(Stdio::null());
+                None
+            }
+        };
+
+        let started = Instant::now();
+        let mut child = cmd.spawn().map_err(ChallengeExecutionError::SpawnFailed)?;
+
+        let stdin_writer = match stdin_input {
+            Some(input) => {
+                let child_stdin = child
+                    .stdin
+                    .take()
+                    .ok_or(ChallengeExecutionError::ClosedStdin)?;
+                Some(spawn_stdin_writer(child_stdin, input))
             }
-            None => cmd.spawn().map_err(ChallengeExecutionError::SpawnFailed)?,
+            None => None,
+        };
+        let child_out = child
+            .stdout
+            .take()
+            .ok_or(ChallengeExecutionError::ClosedStdout)?;
+        let stdout_reader = spawn_pipe_reader(child_out);
+        let child_err = child
+            .stderr
+            .take()
+            .ok_or(ChallengeExecutionError::ClosedStderr)?;
+        let stderr_reader = match self.live_stderr_sink() {
+            Some(sink) => spawn_tee_reader(child_err, sink.clone()),
+            None => spawn_pipe_reader(child_err),
         };
 
-        child.wait()?;
+        let status = wait_with_timeout(&mut child, started, self.config.timeout.map(Into::into))?;
+        let duration = started.elapsed();
+
+        if let Some(writer) = stdin_writer {
+            join_pipe(writer).map_err(ChallengeExecutionError::CouldNotWriteStdin)?;
+        }
+        let output =
+            join_pipe(stdout_reader).map_err(ChallengeExecutionError::CouldNotReadStdout)?;
+        let errors =
+            join_pipe(stderr_reader).map_err(ChallengeExecutionError::CouldNotReadStderr)?;
+
+        Ok(CaseOutput {
+            status,
+            stdout: output,
+            stderr: errors,
+            duration,
+        })
+    }
+
+    /// Names of the parts leading to this case from the root of its challenge.
+    pub fn parent_path(&self) -> &[String] {
+        &self.parent_path
+    }
+
+    pub fn origin(&self) -> &CaseOrigin {
+        &self.origin
+    }
+
+    /// Directory this case reads its files from: that of the file it was
+    /// declared in.
+    pub fn dir<P: AsRef<Path>>(&self, challenge_dir: P) -> PathBuf {
+        join_relative(challenge_dir.as_ref(), self.origin.dir())
+    }
+
+    /// Directory this case runs in: that of its challenge, even if it was
+    /// declared in an included file. A file run on its own or as a workspace
+    /// member is its own challenge, so the same case may run elsewhere when
+    /// it's included.
+    pub fn run_dir<P: AsRef<Path>>(&self, challenge_dir: P) -> PathBuf {
+        join_relative(challenge_dir.as_ref(), &self.origin.challenge_dir)
+    }
+
+    pub fn config(&self) -> &CommandConfig {
+        &self.config
+    }
+
+    pub fn stdin(&self) -> Option<&StringReference> {
+        self.stdin.as_ref()
+    }
+
+    /// Every variable filled in for this case, built-in ones included.
+    pub fn vars(&self) -> &HashMap<String, String> {
+        &self.vars
+    }
 
-        let mut child_out = child.stdout.ok_or(ChallengeExecutionError::ClosedStdout)?;
-        let mut output = String::new();
-        child_out
-            .read_to_string(&mut output)
-            .map_err(ChallengeExecutionError::CouldNotReadStdout)?;
+    /// The program and arguments this case runs, after templating.
+    pub fn command_line(
+        &self,
+        command: &ChallengeCommand,
+    ) -> Result<Vec<String>, CommandParseError> {
+        let mut words = command_words(&command.get_command_with(&self.vars)?);
+        words.extend(self.config.arguments.iter().flatten().cloned());
+        Ok(words)
+    }
 
-        println!("{output}");
+    /// A shell line that runs this case the way [`ChallengeCase::run`] does:
+    /// in its directory, with its environment overrides and stdin.
+    pub fn shell_line<P: AsRef<Path>>(
+        &self,
+        challenge_dir: P,
+        command: &ChallengeCommand,
+    ) -> Result<String, ChallengeExecutionError> {
+        let dir = std::path::absolute(self.run_dir(challenge_dir))?;
+        let mut line = format!("(cd {} && ", shell_quote(&dir.to_string_lossy())?);
+        if let Some(StringReference::Immediate(input)) = &self.stdin {
+            line += &format!("printf '%s' {} | ", shell_quote(input)?);
+        }
+        if let Some(env_vars) = &self.config.environment {
+            let mut assignments = env_vars
+                .iter()
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>();
+            assignments.sort();
+            line += "env ";
+            for assignment in assignments {
+                line += &format!("{} ", shell_quote(&assignment)?);
+            }
+        }
+        let words = self
+            .command_line(command)?
+            .iter()
+            .map(|word| shell_quote(word))
+            .collect::<Result<Vec<_>, _>>()?;
+        line += &words.join(" ");
+        match &self.stdin {
+            Some(StringReference::File { file }) => {
+                let file = self.origin.dir().join(file);
+                let file = file
+                    .strip_prefix(&self.origin.challenge_dir)
+                    .unwrap_or(&file);
+                line += &format!(" < {}", shell_quote(&file.to_string_lossy())?);
+            }
+            Some(StringReference::Immediate(_)) => {}
+            None => line += " < /dev/null",
+        }
+        line.push(')');
+        Ok(line)
+    }
 
-        if let Some(expected) = self.expected {
-            let actual_output = output.trim();
-            let expected_content: String = expected.stdout.into_string(challenge_dir)?;
-            if expected_content.trim() != actual_output {
+    /// The checker's program and arguments for this case, after templating.
+    pub fn checker_line(&self) -> Result<Option<Vec<String>>, CommandParseError> {
+        self.expected
+            .as_ref()
+            .and_then(|e| e.checker.as_ref())
+            .map(|checker| Ok(command_words(&checker.get_command_with(&self.vars)?)))
+            .transpose()
+    }
+
+    /// Path of the group or part whose cases must run one at a time, if any.
+    pub fn serial_group(&self) -> Option<&[String]> {
+        self.serial_group.as_deref()
+    }
+
+    /// Copy stderr to `sink` as it's written in [`StderrMode::Live`]. Without
+    /// a sink, it's only captured.
+    pub fn stream_stderr_to(mut self, sink: StderrSink) -> Self {
+        self.stderr_sink = Some(sink);
+        self
+    }
+
+    fn live_stderr_sink(&self) -> Option<&StderrSink> {
+        let live = self.config.stderr.unwrap_or_default() == StderrMode::Live;
+        self.stderr_sink.as_ref().filter(|_| live)
+    }
+
+    pub fn expected(&self) -> Option<&ChallengeExpectation> {
+        self.expected.as_ref()
+    }
+
+    fn check_expectations<P: AsRef<Path>>(
+        &self,
+        challenge_dir: P,
+        matchers: &MatcherRegistry,
+        status: ExitStatus,
+        output: &str,
+        errors: &str,
+    ) -> Result<(), ChallengeExecutionError> {
+        let dir = self.dir(&challenge_dir);
+        let expected = self.expected.clone().unwrap_or_default();
+        if !expected.exit_code.matches(&status) {
+            return Err(ChallengeExecutionError::UnexpectedExitStatus {
+                expected: expected.exit_code,
+                status,
+                stderr: errors.to_owned(),
+            });
+        }
+
+        let expected_stdout = match &expected.checker {
+            Some(checker) => {
+                let input = CheckerInput {
+                    stdin: self.stdin.as_ref(),
+                    output,
+                    reference: expected.stdout.as_ref().map(|o| &o.content),
+                };
+                run_checker(
+                    checker,
+                    &self.vars,
+                    self.run_dir(&challenge_dir),
+                    &dir,
+                    input,
+                    self.config.timeout.map(Into::into),
+                )?;
+                None
+            }
+            None => expected.stdout,
+        };
+
+        let streams = [
+            (OutputStream::Stdout, expected_stdout, output),
+            (OutputStream::Stderr, expected.stderr, errors),
+        ];
+        for (stream, expected_output, actual) in streams {
+            let Some(expected_output) = expected_output else {
+                continue;
+            };
+            let Some(matcher) = matchers.get(&expected_output.matcher) else {
+                return Err(ChallengeExecutionError::UnknownMatcher(
+                    expected_output.matcher,
+                ));
+            };
+            let expected_content: String = expected_output.content.into_string(&dir)?;
+            if !matcher.matches(&expected_content, actual, &expected_output.options)? {
+                let diff = if matcher.is_whitespace_sensitive() {
+                    OutputDiff::new(&expected_content, actual)
+                } else {
+                    OutputDiff::new(
+                        &format!("{}\n", expected_content.trim()),
+                        &format!("{}\n", actual.trim()),
+                    )
+                };
                 return Err(ChallengeExecutionError::UnexpectedOutput {
+                    stream,
                     expected: expected_content,
-                    actual: actual_output.to_owned(),
+                    actual: actual.trim().to_owned(),
+                    diff,
                 });
-            } else {
-                println!("Matched expected output!");
             }
         }
 
@@ -100,6 +481,19 @@ impl ChallengeCase {
     }
 }
 
+fn shell_quote(word: &str) -> Result<String, CommandParseError> {
+    shlex::try_quote(word)
+        .map(|quoted| quoted.into_owned())
+        .map_err(|_| CommandParseError::Unquotable(word.to_owned()))
+}
+
+fn command_words(cmd: &Command) -> Vec<String> {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect()
+}
+
 #[derive(Debug, Clone)]
 pub enum ChallengeCommandScript {
     Shell(String),
@@ -116,6 +510,16 @@ impl ChallengeCommand {
         part_name: &str,
         case_name: &str,
     ) -> Result<Command, CommandParseError> {
+        self.get_command_with(&HashMap::from([("part", part_name), ("case", case_name)]))
+    }
+
+    /// Build the command, filling in the variables in `vars` if it's
+    /// templated.
+    pub fn get_command_with<K, V>(&self, vars: &HashMap<K, V>) -> Result<Command, CommandParseError>
+    where
+        K: std::borrow::Borrow<str> + Eq + std::hash::Hash,
+        V: AsRef<str>,
+    {
         let mut command_array: VecDeque<String> = match &self.script {
             ChallengeCommandScript::Shell(s) => shlex::split(s)
                 .ok_or(CommandParseError::MalformedString(s.clone()))?
@@ -124,11 +528,9 @@ impl ChallengeCommand {
         };
 
         if self.template {
-            let context = HashMap::from([("part", part_name), ("case", case_name)]);
-
             command_array = command_array
                 .into_iter()
-                .map(|c| template_string(&c, &context))
+                .map(|c| template_string(&c, vars))
                 .collect();
         }
 
@@ -148,6 +550,10 @@ pub struct ChallengeConfigGroup {
     parts: Vec<ChallengeConfig>,
 }
 impl ChallengeConfigGroup {
+    pub fn parts(&self) -> &[ChallengeConfig] {
+        &self.parts
+    }
+
     pub fn enumerate_cases(
         &self,
     ) -> Result<Vec<(ChallengeCommand, ChallengeCase)>, ChallengeCaseError> {
@@ -168,6 +574,10 @@ pub struct ChallengeConfigPart {
     cases: Vec<ChallengeCase>,
 }
 impl ChallengeConfigPart {
+    pub fn cases(&self) -> &[ChallengeCase] {
+        &self.cases
+    }
+
     pub fn enumerate_cases(
         &self,
     ) -> Result<Vec<(ChallengeCommand, ChallengeCase)>, ChallengeCaseError> {
@@ -185,9 +595,127 @@ pub enum ChallengeConfig {
     Part(ChallengeConfigPart),
 }
 impl ChallengeConfig {
-    pub fn parse_file<R: std::io::Read>(reader: R) -> Result<Self, ChallengeParseError> {
-        let data: ChallengeConfigData = serde_yaml::from_reader(reader)?;
-        ChallengeConfig::try_from(data)
+    /// Parse a YAML challenge file from `reader`, reading included files
+    /// relative to the working directory.
+    pub fn parse_file<R: std::io::Read>(mut reader: R) -> Result<Self, ChallengeParseError> {
+        let mut source = String::new();
+        reader.read_to_string(&mut source)?;
+        Self::parse_str(&source)
+    }
+
+    /// Parse a challenge file's contents, reading included files relative to
+    /// the working directory.
+    pub fn parse_str(source: &str) -> Result<Self, ChallengeParseError> {
+        Self::parse_str_in(source, ".")
+    }
+
+    /// Parse a challenge file's contents, reading included files relative to
+    /// `challenge_dir`. Malformed files are reported with the location of the
+    /// offending entry.
+    pub fn parse_str_in<P: AsRef<Path>>(
+        source: &str,
+        challenge_dir: P,
+    ) -> Result<Self, ChallengeParseError> {
+        Self::parse_str_as(source, ConfigFormat::Yaml, challenge_dir)
+    }
+
+    /// Like [`ChallengeConfig::parse_str_in`], for a file in any format.
+    pub fn parse_str_as<P: AsRef<Path>>(
+        source: &str,
+        format: ConfigFormat,
+        challenge_dir: P,
+    ) -> Result<Self, ChallengeParseError> {
+        ChallengeConfigData::parse(source, format)?.resolve(challenge_dir.as_ref())
+    }
+
+    /// Every challenge file under `workspace_dir`, as groups named after
+    /// their directories. `settings` holds the contents of the workspace
+    /// file, if there is one, written in `format`.
+    pub fn parse_workspace<P: AsRef<Path>>(
+        settings: Option<&str>,
+        format: ConfigFormat,
+        workspace_dir: P,
+    ) -> Result<Self, ChallengeParseError> {
+        let workspace_dir = workspace_dir.as_ref();
+        let data = settings
+            .map(|settings| WorkspaceData::parse(settings, format))
+            .transpose()?
+            .unwrap_or_default();
+        let default_name = std::path::absolute(workspace_dir)
+            .ok()
+            .and_then(|dir| {
+                dir.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "workspace".to_owned());
+        data.resolve(default_name, workspace_dir)
+    }
+
+    /// The challenge file at `file`, relative to `workspace_dir`, on its own
+    /// but with what it inherits from the workspace file, whose contents
+    /// `settings` holds.
+    pub fn parse_member<P: AsRef<Path>, Q: AsRef<Path>>(
+        settings: Option<&str>,
+        format: ConfigFormat,
+        workspace_dir: P,
+        file: Q,
+    ) -> Result<Self, ChallengeParseError> {
+        settings
+            .map(|settings| WorkspaceData::parse(settings, format))
+            .transpose()?
+            .unwrap_or_default()
+            .resolve_member(workspace_dir.as_ref(), file.as_ref())
+    }
+
+    /// Whether `source` has the shape of a challenge file, without resolving
+    /// it. Included files may leave out what the including file provides.
+    pub(crate) fn validate_str(
+        source: &str,
+        format: ConfigFormat,
+    ) -> Result<(), ChallengeParseError> {
+        ChallengeConfigData::parse(source, format).map(|_| ())
+    }
+
+    /// Every problem in a challenge file's contents, without stopping at the
+    /// first. `challenge_dir` is where the file's cases would run.
+    pub fn check_str<P: AsRef<Path>>(source: &str, challenge_dir: P) -> Vec<ConfigDiagnostic> {
+        Self::check_str_as(source, ConfigFormat::Yaml, challenge_dir)
+    }
+
+    /// Like [`ChallengeConfig::check_str`], for a file in any format.
+    pub fn check_str_as<P: AsRef<Path>>(
+        source: &str,
+        format: ConfigFormat,
+        challenge_dir: P,
+    ) -> Vec<ConfigDiagnostic> {
+        lint::check(source, format, challenge_dir.as_ref())
+    }
+
+    /// Every problem in the challenge files under `workspace_dir`, like
+    /// [`ChallengeConfig::parse_workspace`] would find them.
+    pub fn check_workspace<P: AsRef<Path>>(
+        settings: Option<&str>,
+        format: ConfigFormat,
+        workspace_dir: P,
+    ) -> Vec<ConfigDiagnostic> {
+        lint::check_workspace(settings, format, workspace_dir.as_ref())
+    }
+
+    /// Every problem in the challenge file at `file`, like
+    /// [`ChallengeConfig::parse_member`] would find them.
+    pub fn check_member<P: AsRef<Path>, Q: AsRef<Path>>(
+        settings: Option<&str>,
+        format: ConfigFormat,
+        workspace_dir: P,
+        file: Q,
+    ) -> Vec<ConfigDiagnostic> {
+        lint::check_member(settings, format, workspace_dir.as_ref(), file.as_ref())
+    }
+
+    /// Whether `file`, relative to `workspace_dir`, is one of the challenge
+    /// files of that workspace.
+    pub fn is_workspace_member<P: AsRef<Path>, Q: AsRef<Path>>(workspace_dir: P, file: Q) -> bool {
+        include::is_member(workspace_dir.as_ref(), file.as_ref())
     }
 
     pub fn get_name(&self) -> &str {
@@ -206,22 +734,33 @@ impl ChallengeConfig {
         }
     }
 
+    /// Resolve case arguments, either as path selectors like `day03/part2/*`
+    /// or as one name per level of the tree.
+    pub fn select_cases(
+        &self,
+        args: &[String],
+    ) -> Result<Vec<(ChallengeCommand, ChallengeCase)>, ChallengeCaseError> {
+        if is_selector_syntax(args) {
+            select_cases(self, args)
+        } else {
+            self.resolve_cases(args.iter().cloned(), CommandConfig::default())
+        }
+    }
+
+    /// Like [`ChallengeConfig::select_cases`], requiring exactly one case.
+    pub fn select_case(
+        &self,
+        args: &[String],
+    ) -> Result<(ChallengeCommand, ChallengeCase), ChallengeCaseError> {
+        single_case(self.select_cases(args)?)
+    }
+
     pub fn resolve_case<I: Iterator<Item = String>>(
         &self,
         cases: I,
         config: CommandConfig,
     ) -> Result<(ChallengeCommand, ChallengeCase), ChallengeCaseError> {
-        let mut cases = self.resolve_cases(cases, config)?;
-
-        if cases.len() > 1 {
-            Err(ChallengeCaseError::TooManyCases(
-                cases[0].1.parent_name.clone(),
-            ))
-        } else if let Some(case) = cases.pop() {
-            Ok(case)
-        } else {
-            Err(ChallengeCaseError::NotEnoughCases)
-        }
+        single_case(self.resolve_cases(cases, config)?)
     }
 
     pub fn resolve_cases<I: Iterator<Item = String>>(
@@ -240,9 +779,14 @@ impl ChallengeConfig {
                     .parts
                     .iter()
                     .find(|c| c.get_name() == next_part)
-                    .ok_or_else(|| ChallengeCaseError::CaseNotFound {
-                        case: next_part,
-                        config_name: parent.name.clone(),
+                    .ok_or_else(|| {
+                        let names = || parent.parts.iter().map(ChallengeConfig::get_name);
+                        ChallengeCaseError::CaseNotFound {
+                            suggestions: did_you_mean(&next_part, names()),
+                            available: listable(names()),
+                            case: next_part,
+                            config_name: parent.name.clone(),
+                        }
                     })?;
                 next_config.resolve_cases(cases, config)
             }
@@ -251,9 +795,14 @@ impl ChallengeConfig {
                     .cases
                     .iter()
                     .find(|c| c.name == next_part)
-                    .ok_or_else(|| ChallengeCaseError::CaseNotFound {
-                        case: next_part,
-                        config_name: leaf.name.clone(),
+                    .ok_or_else(|| {
+                        let names = || leaf.cases.iter().map(|c| c.name.as_str());
+                        ChallengeCaseError::CaseNotFound {
+                            suggestions: did_you_mean(&next_part, names()),
+                            available: listable(names()),
+                            case: next_part,
+                            config_name: leaf.name.clone(),
+                        }
                     })
                     .cloned()?;
 
@@ -264,3 +813,26 @@ impl ChallengeConfig {
         }
     }
 }
+
+fn single_case(
+    mut cases: Vec<(ChallengeCommand, ChallengeCase)>,
+) -> Result<(ChallengeCommand, ChallengeCase), ChallengeCaseError> {
+    if cases.len() > 1 {
+        Err(ChallengeCaseError::TooManyCases(
+            cases[0].1.parent_name.clone(),
+        ))
+    } else if let Some(case) = cases.pop() {
+        Ok(case)
+    } else {
+        Err(ChallengeCaseError::NotEnoughCases)
+    }
+}
+
+/// `dir` inside `base`, or `base` itself if `dir` is empty.
+fn join_relative(base: &Path, dir: &Path) -> PathBuf {
+    if dir.as_os_str().is_empty() {
+        base.to_owned()
+    } else {
+        base.join(dir)
+    }
+}
@@ -0,0 +1,105 @@
+use std::process::ExitStatus;
+use std::time::Duration;
+
+use crate::challenge::{ChallengeExecutionError, StderrMode};
+
+#[derive(Debug)]
+pub enum CaseStatus {
+    Passed,
+    Failed(ChallengeExecutionError),
+    /// Not run, for the given reason.
+    Skipped(String),
+}
+
+/// Everything known about a case after trying to run it.
+#[derive(Debug)]
+pub struct CaseOutcome {
+    pub parent_path: Vec<String>,
+    pub name: String,
+    pub stdout: String,
+    pub stderr: String,
+    /// `None` if the command didn't run to completion.
+    pub exit_status: Option<ExitStatus>,
+    pub duration: Duration,
+    pub stderr_mode: StderrMode,
+    /// Whether stderr was already passed through while the case ran.
+    pub stderr_streamed: bool,
+    pub status: CaseStatus,
+}
+impl CaseOutcome {
+    pub fn skipped(parent_path: Vec<String>, name: String, reason: String) -> Self {
+        Self {
+            parent_path,
+            name,
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_status: None,
+            duration: Duration::ZERO,
+            stderr_mode: StderrMode::default(),
+            stderr_streamed: false,
+            status: CaseStatus::Skipped(reason),
+        }
+    }
+
+    pub fn is_passed(&self) -> bool {
+        matches!(self.status, CaseStatus::Passed)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self.status, CaseStatus::Failed(_))
+    }
+
+    /// Turn a failed outcome into its error.
+    pub fn into_result(mut self) -> Result<Self, ChallengeExecutionError> {
+        match std::mem::replace(&mut self.status, CaseStatus::Passed) {
+            CaseStatus::Failed(err) => Err(err),
+            status => {
+                self.status = status;
+                Ok(self)
+            }
+        }
+    }
+}
+
+/// Outcomes of every case in a run, in the order they were run.
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub cases: Vec<CaseOutcome>,
+}
+impl RunReport {
+    pub fn passed(&self) -> usize {
+        self.cases.iter().filter(|c| c.is_passed()).count()
+    }
+
+    /// Cases that ran but didn't behave as expected.
+    pub fn failed(&self) -> usize {
+        self.count_failures(true)
+    }
+
+    /// Cases that couldn't be run or checked.
+    pub fn errored(&self) -> usize {
+        self.count_failures(false)
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|c| matches!(c.status, CaseStatus::Skipped(_)))
+            .count()
+    }
+
+    /// Whether no case failed or errored.
+    pub fn is_success(&self) -> bool {
+        !self.cases.iter().any(CaseOutcome::is_failed)
+    }
+
+    fn count_failures(&self, test_failure: bool) -> usize {
+        self.cases
+            .iter()
+            .filter(|c| match &c.status {
+                CaseStatus::Failed(err) => err.is_test_failure() == test_failure,
+                _ => false,
+            })
+            .count()
+    }
+}
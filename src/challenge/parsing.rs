@@ -1,77 +1,347 @@
 use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
 
-use serde::Deserialize;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
+use serde::de::{Error as _, IgnoredAny};
+use serde::{Deserialize, Deserializer};
 
-use super::misc::StringReference;
+use super::diagnose::diagnose;
+use super::include::{discover, find_includes, include_name, is_pattern, WorkspaceEntry};
+use super::misc::{CaseOrder, StringReference};
 use crate::challenge::command::CommandConfig;
 use crate::challenge::{
-    ChallengeCase, ChallengeCommand, ChallengeCommandScript, ChallengeConfig, ChallengeConfigGroup,
-    ChallengeConfigPart, ChallengeExpectation, ChallengeParseError,
+    CaseOrigin, ChallengeCase, ChallengeCommand, ChallengeCommandScript, ChallengeConfig,
+    ChallengeConfigGroup, ChallengeConfigPart, ChallengeExpectation, ChallengeParseError,
+    ConfigDiagnostic, ConfigFormat, ExitCodeExpectation, MatchOptions, OutputExpectation,
+    BUILTIN_VARS, DEFAULT_MATCHER,
 };
+use crate::template::template_string;
+
+/// Where a node sits in the challenge, and in the file it was declared in.
+#[derive(Debug, Clone)]
+pub(super) struct Location {
+    /// Names of the parts leading to this node from the root of the challenge.
+    path: Vec<String>,
+    origin: CaseOrigin,
+    /// Directory of the challenge file, which included files are found from.
+    challenge_dir: PathBuf,
+    /// Name of the challenge this node belongs to. Unset above the challenge
+    /// files of a workspace.
+    challenge_name: Option<String>,
+    /// Files included on the way to this node, to catch includes that loop.
+    included: Vec<PathBuf>,
+}
+impl Location {
+    pub(super) fn root(challenge_dir: &Path, challenge_name: Option<String>) -> Self {
+        Self {
+            path: Vec::new(),
+            origin: CaseOrigin::default(),
+            challenge_dir: challenge_dir.to_owned(),
+            challenge_name,
+            included: Vec::new(),
+        }
+    }
+
+    /// The built-in variables of the case `case_name` under this node.
+    pub(super) fn builtin_vars(&self, part_name: &str, case_name: &str) -> HashMap<String, String> {
+        let dir = self.challenge_dir.join(&self.origin.challenge_dir);
+        let dir = std::path::absolute(&dir).unwrap_or(dir);
+        let path = [self.path.as_slice(), &[case_name.to_owned()]].concat();
+        HashMap::from([
+            ("part".to_owned(), part_name.to_owned()),
+            ("case".to_owned(), case_name.to_owned()),
+            ("path".to_owned(), path.join("/")),
+            (
+                "challenge_dir".to_owned(),
+                dir.to_string_lossy().into_owned(),
+            ),
+            (
+                "challenge_name".to_owned(),
+                self.challenge_name.clone().unwrap_or_default(),
+            ),
+        ])
+    }
+
+    pub(super) fn child(&self, name: &str) -> Self {
+        let mut child = self.clone();
+        child.path.push(name.to_owned());
+        child.origin.parent_path.push(name.to_owned());
+        child
+    }
+
+    /// The root of the included file `file`, relative to the challenge
+    /// directory, when it's included at this node.
+    pub(super) fn in_file(mut self, file: PathBuf) -> Self {
+        self.origin.file = Some(file);
+        self.origin.parent_path.clear();
+        self
+    }
+
+    /// Where a challenge of a workspace sits, given its file relative to the
+    /// workspace directory. Its cases run in that file's directory.
+    pub(super) fn in_challenge(mut self, file: &Path) -> Self {
+        self.origin.challenge_dir = file.parent().unwrap_or(Path::new("")).to_owned();
+        self
+    }
+
+    /// Directory included files and files referred to are found from.
+    pub(super) fn files_dir(&self) -> PathBuf {
+        self.challenge_dir.join(self.origin.dir())
+    }
+
+    /// Directory of the file this node is declared in, relative to the
+    /// challenge directory.
+    pub(super) fn origin_dir(&self) -> &Path {
+        self.origin.dir()
+    }
+
+    /// Name the challenge `name`, unless it's named already.
+    pub(super) fn named(mut self, name: &str) -> Self {
+        self.challenge_name.get_or_insert_with(|| name.to_owned());
+        self
+    }
+}
 
 trait TryResolveChallenge<T>
 where
     Self: Sized,
 {
+    /// `at` is where this node sits, and `serial_group` the path of the
+    /// nearest node that disabled parallel execution.
     fn try_resolve(
         self,
         name: String,
+        at: Location,
+        serial_group: Option<Vec<String>>,
+        inherit_order: CaseOrder,
         inherit_command: Option<ChallengeCommandData>,
         inherit_config: CommandConfig,
     ) -> Result<T, ChallengeParseError>;
 
-    fn try_resolve_default(self, name: String) -> Result<T, ChallengeParseError> {
-        self.try_resolve(name, None, CommandConfig::default())
+    fn try_resolve_root(
+        self,
+        name: String,
+        challenge_dir: &Path,
+    ) -> Result<T, ChallengeParseError> {
+        let at = Location::root(challenge_dir, Some(name.clone()));
+        self.try_resolve(
+            name,
+            at,
+            None,
+            CaseOrder::default(),
+            None,
+            CommandConfig::default(),
+        )
     }
 }
 trait TryResolveCase<T> {
+    /// `command` is the command the case runs, whose `template` setting
+    /// also applies to immediate stdin.
     fn try_resolve(
         self,
         case_name: String,
         parent_name: String,
+        parent: &Location,
+        serial_group: Option<Vec<String>>,
+        command: &ChallengeCommand,
         inherit_config: CommandConfig,
     ) -> Result<T, ChallengeParseError>;
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct ChallengeExpectationData {
-    stdout: StringReference,
+/// Rejects whatever keys are left once every other field of a struct has
+/// taken its own. Stands in for `deny_unknown_fields`, which doesn't work
+/// together with `flatten`.
+#[derive(Debug, Clone, Default)]
+struct NoUnknownFields;
+impl<'de> Deserialize<'de> for NoUnknownFields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let leftover = IndexMap::<String, IgnoredAny>::deserialize(deserializer)?;
+        match leftover.keys().next() {
+            Some(key) => Err(D::Error::custom(format!("unknown field `{key}`"))),
+            None => Ok(NoUnknownFields),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(expecting = "a matcher", deny_unknown_fields)]
+#[schemars(
+    rename = "Matcher",
+    extend("oneOf" = [{ "required": ["value"] }, { "required": ["file"] }])
+)]
+pub(super) struct MatchedOutputData {
+    /// How to compare the output with the expected content. Built-in matchers
+    /// are listed, but library users may register their own.
+    #[serde(rename = "match")]
+    #[schemars(extend("anyOf" = [
+        { "enum": ["exact", "trimmed", "regex", "contains", "lines-unordered", "float"] },
+        { "type": "string" },
+    ]))]
+    matcher: String,
+    /// The expected content (or pattern, for 'regex')
+    value: Option<String>,
+    /// Path of the file to load the expected content from (relative to this
+    /// challenge file)
+    file: Option<String>,
+    /// Absolute tolerance for the 'float' matcher
+    #[schemars(range(min = 0.0))]
+    absolute: Option<f64>,
+    /// Relative tolerance for the 'float' matcher
+    #[schemars(range(min = 0.0))]
+    relative: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(untagged)]
+#[schemars(rename = "OutputExpectation")]
+pub(super) enum OutputExpectationData {
+    Matched(MatchedOutputData),
+    Reference(StringReference),
 }
-impl From<ChallengeExpectationData> for ChallengeExpectation {
-    fn from(value: ChallengeExpectationData) -> Self {
-        ChallengeExpectation {
-            stdout: value.stdout,
+impl OutputExpectationData {
+    fn try_resolve(self, case_name: &str) -> Result<OutputExpectation, ChallengeParseError> {
+        match self {
+            OutputExpectationData::Reference(content) => Ok(OutputExpectation {
+                content,
+                matcher: DEFAULT_MATCHER.to_owned(),
+                options: MatchOptions::default(),
+            }),
+            OutputExpectationData::Matched(data) => {
+                let content = match (data.value, data.file) {
+                    (Some(value), None) => StringReference::Immediate(value),
+                    (None, Some(file)) => StringReference::File { file },
+                    _ => return Err(ChallengeParseError::InvalidMatchValue(case_name.to_owned())),
+                };
+                Ok(OutputExpectation {
+                    content,
+                    matcher: data.matcher,
+                    options: MatchOptions {
+                        absolute: data.absolute,
+                        relative: data.relative,
+                    },
+                })
+            }
         }
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct ChallengeCaseData {
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Expectation")]
+pub(super) struct ChallengeExpectationData {
+    /// Data expected on stdout after running this case
+    stdout: Option<OutputExpectationData>,
+    /// Data expected on stderr after running this case
+    stderr: Option<OutputExpectationData>,
+    /// Exit status expected after running this case (default: 0)
+    exit_code: Option<ExitCodeExpectation>,
+    /// Command that decides whether stdout is correct by its exit code. It
+    /// receives the paths of the case's input, actual output and reference
+    /// answer (taken from 'stdout', if given) in CHALLENGE_INPUT,
+    /// CHALLENGE_OUTPUT and CHALLENGE_EXPECTED.
+    checker: Option<ChallengeCommandData>,
+}
+impl ChallengeExpectationData {
+    /// Only the paths of expected files are templated, not expected content.
+    fn try_resolve(
+        self,
+        case_name: &str,
+        vars: &HashMap<String, String>,
+    ) -> Result<ChallengeExpectation, ChallengeParseError> {
+        let resolve = |output: OutputExpectationData| {
+            let mut output = output.try_resolve(case_name)?;
+            output.content = output.content.templated(vars, false);
+            Ok::<_, ChallengeParseError>(output)
+        };
+        Ok(ChallengeExpectation {
+            stdout: self.stdout.map(resolve).transpose()?,
+            stderr: self.stderr.map(resolve).transpose()?,
+            exit_code: self.exit_code.unwrap_or_default(),
+            checker: self.checker.map(ChallengeCommandData::into),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[schemars(rename = "Case", deny_unknown_fields)]
+pub(super) struct ChallengeCaseData {
     #[serde(flatten)]
     config: CommandConfig,
+    /// Data to pass to the stdin when running this case
     stdin: Option<StringReference>,
+    /// Expected output for this challenge case
     expected: Option<ChallengeExpectationData>,
+    #[serde(flatten)]
+    #[schemars(skip)]
+    _unknown: NoUnknownFields,
 }
 impl TryResolveCase<ChallengeCase> for ChallengeCaseData {
     fn try_resolve(
         self,
         case_name: String,
         parent_name: String,
+        parent: &Location,
+        serial_group: Option<Vec<String>>,
+        command: &ChallengeCommand,
         inherit_config: CommandConfig,
     ) -> Result<ChallengeCase, ChallengeParseError> {
+        let mut config = inherit_config.merge(&self.config);
+        let own_vars = config.vars.clone().unwrap_or_default();
+        if let Some(name) = own_vars
+            .keys()
+            .find(|name| BUILTIN_VARS.contains(&name.as_str()))
+        {
+            return Err(ChallengeParseError::BuiltinVariable(name.clone()));
+        }
+        let mut vars = parent.builtin_vars(&parent_name, &case_name);
+        vars.extend(own_vars);
+
+        let stdin = self
+            .stdin
+            .map(|stdin| stdin.templated(&vars, command.template));
+        if let Some(StringReference::File { file }) = &stdin {
+            let input_file = parent.files_dir().join(file);
+            let input_file = std::path::absolute(&input_file).unwrap_or(input_file);
+            vars.insert(
+                "input_file".to_owned(),
+                input_file.to_string_lossy().into_owned(),
+            );
+        }
+        if let Some(arguments) = &mut config.arguments {
+            for argument in arguments {
+                *argument = template_string(argument, &vars);
+            }
+        }
+        if let Some(environment) = &mut config.environment {
+            for value in environment.values_mut() {
+                *value = template_string(value, &vars);
+            }
+        }
+        let expected = self
+            .expected
+            .map(|e| e.try_resolve(&case_name, &vars))
+            .transpose()?;
         Ok(ChallengeCase {
             name: case_name,
             parent_name,
-            config: inherit_config.merge(&self.config),
-            stdin: self.stdin,
-            expected: self.expected.map(ChallengeExpectationData::into),
+            parent_path: parent.path.clone(),
+            origin: parent.origin.clone(),
+            serial_group,
+            stderr_sink: None,
+            config,
+            vars,
+            stdin,
+            expected,
         })
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(untagged)]
-enum ChallengeCommandScriptData {
+#[schemars(rename = "CommandScript")]
+pub(super) enum ChallengeCommandScriptData {
     Shell(String),
     Exec(Vec<String>),
 }
@@ -84,9 +354,13 @@ impl From<ChallengeCommandScriptData> for ChallengeCommandScript {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
-struct ChallengeCommandObjectData {
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
+#[serde(expecting = "a command object", deny_unknown_fields)]
+#[schemars(rename = "CommandObject")]
+pub(super) struct ChallengeCommandObjectData {
     script: ChallengeCommandScriptData,
+    /// Whether to fill in variables like '{{part}}' and '{{case}}' in the
+    /// script (default: true)
     template: Option<bool>,
 }
 impl From<ChallengeCommandObjectData> for ChallengeCommand {
@@ -98,9 +372,10 @@ impl From<ChallengeCommandObjectData> for ChallengeCommand {
     }
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, JsonSchema, Clone)]
 #[serde(untagged)]
-enum ChallengeCommandData {
+#[schemars(rename = "Command")]
+pub(super) enum ChallengeCommandData {
     Shell(String),
     Exec(Vec<String>),
     Object(ChallengeCommandObjectData),
@@ -122,29 +397,54 @@ impl From<ChallengeCommandData> for ChallengeCommand {
     }
 }
 
-#[derive(Debug, Deserialize)]
+fn is_serial(parallel: Option<bool>) -> bool {
+    parallel == Some(false)
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(rename = "Group")]
 pub struct ChallengeConfigGroupData {
+    /// The command to run for the cases in this group
     command: Option<ChallengeCommandData>,
     #[serde(flatten)]
     config: CommandConfig,
+    /// Whether nested cases may run at the same time as each other.
+    parallel: Option<bool>,
+    /// The order nested parts and cases run in. Inherited by nested groups.
+    order: Option<CaseOrder>,
 
-    parts: HashMap<String, ChallengeConfigNode>,
+    /// Nested groups and parts
+    parts: IndexMap<String, ChallengeConfigNode>,
+    #[serde(flatten)]
+    #[schemars(skip)]
+    _unknown: NoUnknownFields,
 }
 impl TryResolveChallenge<ChallengeConfigGroup> for ChallengeConfigGroupData {
     fn try_resolve(
         self,
         name: String,
+        at: Location,
+        serial_group: Option<Vec<String>>,
+        inherit_order: CaseOrder,
         inherit_command: Option<ChallengeCommandData>,
         inherit_config: CommandConfig,
     ) -> Result<ChallengeConfigGroup, ChallengeParseError> {
+        let serial_group =
+            serial_group.or_else(|| is_serial(self.parallel).then(|| at.path.clone()));
+        let order = self.order.unwrap_or(inherit_order);
+        let mut parts = self.parts;
+        parts.sort_by(|a, _, b, _| order.compare(a, b));
         Ok(ChallengeConfigGroup {
             name,
-            parts: self
-                .parts
+            parts: parts
                 .into_iter()
                 .map(|(part_name, part_data)| {
+                    let part_at = at.child(&part_name);
                     part_data.try_resolve(
                         part_name,
+                        part_at,
+                        serial_group.clone(),
+                        order,
                         self.command.clone().or(inherit_command.clone()),
                         inherit_config.merge(&self.config),
                     )
@@ -154,81 +454,356 @@ impl TryResolveChallenge<ChallengeConfigGroup> for ChallengeConfigGroupData {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(rename = "Part")]
 pub struct ChallengeConfigPartData {
+    /// The command to run for the cases in this part
     command: Option<ChallengeCommandData>,
     #[serde(flatten)]
     config: CommandConfig,
+    /// Whether this part's cases may run at the same time as each other.
+    parallel: Option<bool>,
+    /// The order this part's cases run in.
+    order: Option<CaseOrder>,
 
-    cases: HashMap<String, ChallengeCaseData>,
+    /// The input/output cases for this part
+    cases: IndexMap<String, ChallengeCaseData>,
+    #[serde(flatten)]
+    #[schemars(skip)]
+    _unknown: NoUnknownFields,
 }
 impl TryResolveChallenge<ChallengeConfigPart> for ChallengeConfigPartData {
     fn try_resolve(
         self,
         name: String,
+        at: Location,
+        serial_group: Option<Vec<String>>,
+        inherit_order: CaseOrder,
         inherit_command: Option<ChallengeCommandData>,
         inherit_config: CommandConfig,
     ) -> Result<ChallengeConfigPart, ChallengeParseError> {
+        let serial_group =
+            serial_group.or_else(|| is_serial(self.parallel).then(|| at.path.clone()));
+        let order = self.order.unwrap_or(inherit_order);
+        let mut cases = self.cases;
+        cases.sort_by(|a, _, b, _| order.compare(a, b));
+        let command: ChallengeCommand = self
+            .command
+            .or(inherit_command)
+            .ok_or(ChallengeParseError::NoCommandFound(name.clone()))?
+            .into();
         Ok(ChallengeConfigPart {
             name: name.clone(),
-            command: self
-                .command
-                .or(inherit_command)
-                .ok_or(ChallengeParseError::NoCommandFound(name.clone()))?
-                .into(),
-            cases: self
-                .cases
+            cases: cases
                 .into_iter()
                 .map(|(case_name, case_data)| {
                     case_data.try_resolve(
                         case_name,
                         name.clone(),
+                        &at,
+                        serial_group.clone(),
+                        &command,
                         inherit_config.merge(&self.config),
                     )
                 })
                 .collect::<Result<_, _>>()?,
+            command,
         })
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
+#[schemars(rename = "Node", extend("unevaluatedProperties" = false))]
 enum ChallengeConfigNode {
     Group(ChallengeConfigGroupData),
     Part(ChallengeConfigPartData),
+    Include(IncludeData),
 }
 impl TryResolveChallenge<ChallengeConfig> for ChallengeConfigNode {
     fn try_resolve(
         self,
         name: String,
+        at: Location,
+        serial_group: Option<Vec<String>>,
+        inherit_order: CaseOrder,
         inherit_command: Option<ChallengeCommandData>,
         inherit_config: CommandConfig,
     ) -> Result<ChallengeConfig, ChallengeParseError> {
         match self {
             ChallengeConfigNode::Group(group) => Ok(ChallengeConfig::Group(group.try_resolve(
                 name,
+                at,
+                serial_group,
+                inherit_order,
                 inherit_command,
                 inherit_config,
             )?)),
             ChallengeConfigNode::Part(part) => Ok(ChallengeConfig::Part(part.try_resolve(
                 name,
+                at,
+                serial_group,
+                inherit_order,
                 inherit_command,
                 inherit_config,
             )?)),
+            ChallengeConfigNode::Include(include) => include.try_resolve(
+                name,
+                at,
+                serial_group,
+                inherit_order,
+                inherit_command,
+                inherit_config,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+#[schemars(rename = "Include")]
+pub(super) struct IncludeData {
+    /// Challenge file or directory to use as this part (relative to this
+    /// challenge file). Each segment may be a glob pattern like 'day*/', which
+    /// makes this a group of every match, named after their directories.
+    /// Included cases read their files from their own directory but run in
+    /// this challenge's, unlike when their file is run on its own, so
+    /// relative commands like './solve' are found from here.
+    include: String,
+}
+impl TryResolveChallenge<ChallengeConfig> for IncludeData {
+    fn try_resolve(
+        self,
+        name: String,
+        at: Location,
+        serial_group: Option<Vec<String>>,
+        inherit_order: CaseOrder,
+        inherit_command: Option<ChallengeCommandData>,
+        inherit_config: CommandConfig,
+    ) -> Result<ChallengeConfig, ChallengeParseError> {
+        let dir = at.origin.dir().to_owned();
+        let mut files = find_includes(&at.challenge_dir.join(&dir), &self.include)?
+            .into_iter()
+            .map(|file| dir.join(file))
+            .collect::<Vec<_>>();
+        if !is_pattern(&self.include) {
+            let file = files.remove(0);
+            return include_file(
+                file,
+                name,
+                at,
+                serial_group,
+                inherit_order,
+                inherit_command,
+                inherit_config,
+            );
         }
+
+        files.sort_by(|a, b| inherit_order.compare(&include_name(a), &include_name(b)));
+        Ok(ChallengeConfig::Group(ChallengeConfigGroup {
+            name,
+            parts: files
+                .into_iter()
+                .map(|file| {
+                    let part_name = include_name(&file);
+                    let part_at = at.child(&part_name);
+                    include_file(
+                        file,
+                        part_name,
+                        part_at,
+                        serial_group.clone(),
+                        inherit_order,
+                        inherit_command.clone(),
+                        inherit_config.clone(),
+                    )
+                })
+                .collect::<Result<_, _>>()?,
+        }))
     }
 }
 
-#[derive(Debug, Deserialize)]
+/// Resolve the challenge file at `file`, relative to the challenge directory,
+/// as the node `name`. Its own name is only used when it runs on its own.
+fn include_file(
+    file: PathBuf,
+    name: String,
+    at: Location,
+    serial_group: Option<Vec<String>>,
+    inherit_order: CaseOrder,
+    inherit_command: Option<ChallengeCommandData>,
+    inherit_config: CommandConfig,
+) -> Result<ChallengeConfig, ChallengeParseError> {
+    let full_path = at.challenge_dir.join(&file);
+    let read_err = |err| ChallengeParseError::CouldNotReadInclude(full_path.clone(), err);
+    let canonical = full_path.canonicalize().map_err(read_err)?;
+    if at.included.contains(&canonical) {
+        return Err(ChallengeParseError::IncludeCycle(full_path));
+    }
+    let source = read_to_string(&full_path).map_err(read_err)?;
+    let data = ChallengeConfigData::parse(&source, ConfigFormat::of_file(&full_path))
+        .map_err(|err| err.in_file(&full_path))?;
+
+    let mut at = at.in_file(file).named(&data.name);
+    at.included.push(canonical);
+    data.node.try_resolve(
+        name,
+        at,
+        serial_group,
+        inherit_order,
+        inherit_command,
+        inherit_config,
+    )
+}
+
+/// A challenge definition, used by the challenge script to execute coding
+/// challenges
+#[derive(Debug, Deserialize, JsonSchema)]
+#[schemars(
+    title = "Challenge",
+    extend(
+        "$id" = "https://raw.githubusercontent.com/scott181182/challenge-script/refs/heads/main/challenge.schema.json",
+        "unevaluatedProperties" = false,
+    )
+)]
 pub struct ChallengeConfigData {
+    /// The name of this challenge
     name: String,
     #[serde(flatten)]
     node: ChallengeConfigNode,
 }
-impl TryFrom<ChallengeConfigData> for ChallengeConfig {
-    type Error = ChallengeParseError;
+impl ChallengeConfigData {
+    /// Parse a challenge file's contents. Malformed files are reported with
+    /// the location of the offending entry.
+    pub(super) fn parse(source: &str, format: ConfigFormat) -> Result<Self, ChallengeParseError> {
+        let document = format.parse(source)?;
+        serde_yaml::from_value(document.clone()).map_err(|err| {
+            ChallengeParseError::Invalid(Box::new(diagnose(source, format, &document, err)))
+        })
+    }
+
+    /// Resolve the tree, reading included files relative to `challenge_dir`.
+    pub(super) fn resolve(
+        self,
+        challenge_dir: &Path,
+    ) -> Result<ChallengeConfig, ChallengeParseError> {
+        self.node.try_resolve_root(self.name, challenge_dir)
+    }
+}
 
-    fn try_from(value: ChallengeConfigData) -> Result<Self, Self::Error> {
-        value.node.try_resolve_default(value.name)
+/// Settings shared by every challenge in a workspace, read from its
+/// workspace file.
+#[derive(Debug, Default, Deserialize)]
+pub(super) struct WorkspaceData {
+    /// The name of this workspace (default: the name of its directory)
+    name: Option<String>,
+    /// The command to run for cases that don't set their own
+    command: Option<ChallengeCommandData>,
+    #[serde(flatten)]
+    config: CommandConfig,
+    /// Whether cases of different challenges may run at the same time.
+    parallel: Option<bool>,
+    /// The order challenges, and their parts and cases, run in.
+    order: Option<CaseOrder>,
+    #[serde(flatten)]
+    _unknown: NoUnknownFields,
+}
+impl WorkspaceData {
+    /// Parse a workspace file's contents. An empty file keeps every default.
+    pub(super) fn parse(source: &str, format: ConfigFormat) -> Result<Self, ChallengeParseError> {
+        let document = format.parse(source)?;
+        serde_yaml::from_value::<Option<Self>>(document)
+            .map(Option::unwrap_or_default)
+            .map_err(|err| Box::new(ConfigDiagnostic::unlocated(err.to_string())).into())
+    }
+
+    pub(super) fn command(&self) -> Option<ChallengeCommand> {
+        self.command.clone().map(Into::into)
+    }
+
+    pub(super) fn vars(&self) -> HashMap<String, String> {
+        self.config.vars.clone().unwrap_or_default()
+    }
+
+    /// Resolve every challenge file under `workspace_dir` as a group named
+    /// after its directory, nested the way the directories are.
+    pub(super) fn resolve(
+        self,
+        default_name: String,
+        workspace_dir: &Path,
+    ) -> Result<ChallengeConfig, ChallengeParseError> {
+        let entries = discover(workspace_dir)?;
+        let name = self.name.clone().unwrap_or(default_name);
+        let inherited = self.into_inherited();
+        Ok(ChallengeConfig::Group(ChallengeConfigGroup {
+            name,
+            parts: resolve_workspace(entries, &Location::root(workspace_dir, None), &inherited)?,
+        }))
     }
+
+    /// Resolve the challenge file at `file`, relative to `workspace_dir`, on
+    /// its own but with what it inherits from the workspace.
+    pub(super) fn resolve_member(
+        self,
+        workspace_dir: &Path,
+        file: &Path,
+    ) -> Result<ChallengeConfig, ChallengeParseError> {
+        let inherited = self.into_inherited();
+        include_file(
+            file.to_owned(),
+            include_name(file),
+            Location::root(workspace_dir, None).in_challenge(file),
+            inherited.serial_group,
+            inherited.order,
+            inherited.command,
+            inherited.config,
+        )
+    }
+
+    fn into_inherited(self) -> Inherited {
+        Inherited {
+            serial_group: is_serial(self.parallel).then(Vec::new),
+            order: self.order.unwrap_or_default(),
+            command: self.command,
+            config: self.config,
+        }
+    }
+}
+
+/// What every challenge in a workspace inherits from its workspace file.
+struct Inherited {
+    serial_group: Option<Vec<String>>,
+    order: CaseOrder,
+    command: Option<ChallengeCommandData>,
+    config: CommandConfig,
+}
+
+fn resolve_workspace(
+    mut entries: Vec<(String, WorkspaceEntry)>,
+    at: &Location,
+    inherited: &Inherited,
+) -> Result<Vec<ChallengeConfig>, ChallengeParseError> {
+    entries.sort_by(|(a, _), (b, _)| inherited.order.compare(a, b));
+    entries
+        .into_iter()
+        .map(|(name, entry)| {
+            let entry_at = at.child(&name);
+            match entry {
+                WorkspaceEntry::Challenge(file) => include_file(
+                    file.clone(),
+                    name,
+                    entry_at.in_challenge(&file),
+                    inherited.serial_group.clone(),
+                    inherited.order,
+                    inherited.command.clone(),
+                    inherited.config.clone(),
+                ),
+                WorkspaceEntry::Directory(children) => {
+                    Ok(ChallengeConfig::Group(ChallengeConfigGroup {
+                        parts: resolve_workspace(children, &entry_at, inherited)?,
+                        name,
+                    }))
+                }
+            }
+        })
+        .collect()
 }
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::process::{Child, Command, ExitStatus};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, sleep, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::challenge::ChallengeExecutionError;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Where the stderr of cases in [`StderrMode::Live`](crate::challenge::StderrMode::Live)
+/// is copied as it's written. Clones share the same writer.
+#[derive(Clone)]
+pub struct StderrSink(Arc<Mutex<dyn Write + Send>>);
+impl StderrSink {
+    pub fn new<W: Write + Send + 'static>(writer: W) -> Self {
+        Self(Arc::new(Mutex::new(writer)))
+    }
+
+    fn lock(&self) -> std::io::Result<MutexGuard<'_, dyn Write + Send + 'static>> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("stderr sink poisoned"))
+    }
+}
+impl fmt::Debug for StderrSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StderrSink")
+    }
+}
+impl Write for StderrSink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.lock()?.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.lock()?.flush()
+    }
+}
+
+/// Start the child in its own process group, so that anything it spawns can
+/// be killed along with it.
+pub fn isolate_process_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Kill the child and every process in its process group.
+pub fn kill_process_group(child: &mut Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let pgid = child.id() as libc::pid_t;
+        // SAFETY: `kill` has no memory-safety preconditions. The child was
+        // started as the leader of its own group, so `-pgid` only targets it
+        // and its descendants.
+        if unsafe { libc::kill(-pgid, libc::SIGKILL) } == 0 {
+            return Ok(());
+        }
+    }
+    child.kill()
+}
+
+/// Wait for the child to exit, killing its process group if it outlives
+/// `limit`.
+pub fn wait_with_timeout(
+    child: &mut Child,
+    started: Instant,
+    limit: Option<Duration>,
+) -> Result<ExitStatus, ChallengeExecutionError> {
+    let Some(limit) = limit else {
+        return Ok(child.wait()?);
+    };
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+
+        let elapsed = started.elapsed();
+        if elapsed >= limit {
+            kill_process_group(child)?;
+            child.wait()?;
+            return Err(ChallengeExecutionError::TimedOut { elapsed, limit });
+        }
+        sleep(POLL_INTERVAL.min(limit - elapsed));
+    }
+}
+
+/// Feed `input` to the child's stdin on a separate thread, so that a child
+/// which writes output before consuming all of its input can't deadlock us.
+pub fn spawn_stdin_writer<W: Write + Send + 'static>(
+    mut stdin: W,
+    input: String,
+) -> JoinHandle<std::io::Result<()>> {
+    thread::spawn(move || match stdin.write_all(input.as_bytes()) {
+        // The child is free to exit without reading all of its input.
+        Err(err) if err.kind() == ErrorKind::BrokenPipe => Ok(()),
+        res => res,
+    })
+}
+
+/// Drain a pipe from the child on a separate thread.
+pub fn spawn_pipe_reader<R: Read + Send + 'static>(pipe: R) -> JoinHandle<std::io::Result<String>> {
+    thread::spawn(move || read_pipe(pipe, None))
+}
+
+/// Drain a pipe from the child on a separate thread, copying everything read
+/// into `sink` as it arrives.
+pub fn spawn_tee_reader<R: Read + Send + 'static, W: Write + Send + 'static>(
+    pipe: R,
+    mut sink: W,
+) -> JoinHandle<std::io::Result<String>> {
+    thread::spawn(move || read_pipe(pipe, Some(&mut sink)))
+}
+
+fn read_pipe<R: Read>(mut pipe: R, mut sink: Option<&mut dyn Write>) -> std::io::Result<String> {
+    let mut output = Vec::new();
+    let mut buf = [0; 8192];
+    loop {
+        let len = match pipe.read(&mut buf) {
+            Ok(0) => break,
+            Ok(len) => len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(sink) = sink.as_mut() {
+            sink.write_all(&buf[..len])?;
+            sink.flush()?;
+        }
+        output.extend_from_slice(&buf[..len]);
+    }
+
+    String::from_utf8(output).map_err(|err| std::io::Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Join a pipe thread, treating a panic on that thread as an I/O error.
+pub fn join_pipe<T>(handle: JoinHandle<std::io::Result<T>>) -> std::io::Result<T> {
+    handle
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("pipe thread panicked")))
+}
@@ -0,0 +1,20 @@
+use schemars::generate::SchemaSettings;
+use schemars::transform::RecursiveTransform;
+use schemars::Schema;
+
+use crate::challenge::parsing::ChallengeConfigData;
+
+/// JSON schema of challenge files, generated from the types they're read into.
+pub fn challenge_schema() -> Schema {
+    SchemaSettings::draft2020_12()
+        .with_transform(RecursiveTransform(unwrap_description))
+        .into_generator()
+        .into_root_schema_for::<ChallengeConfigData>()
+}
+
+/// Descriptions come from doc comments, which are wrapped to fit the source.
+fn unwrap_description(schema: &mut Schema) {
+    if let Some(serde_json::Value::String(description)) = schema.get_mut("description") {
+        *description = description.replace('\n', " ");
+    }
+}
@@ -0,0 +1,143 @@
+use globset::{Glob, GlobBuilder, GlobMatcher};
+
+use crate::challenge::suggest::{did_you_mean, listable};
+use crate::challenge::{ChallengeCase, ChallengeCaseError, ChallengeCommand, ChallengeConfig};
+
+const SELECTOR_CHARS: [char; 5] = ['/', '*', '?', '[', '{'];
+
+/// Whether case arguments are path selectors like `day03/part2/*`, rather
+/// than one name per level of the challenge tree.
+pub fn is_selector_syntax(args: &[String]) -> bool {
+    args.iter().any(|arg| arg.contains(SELECTOR_CHARS))
+}
+
+/// Cases whose path, or the path of a group or part above them, matches
+/// any of `selectors`. Cases are returned in tree order.
+pub fn select_cases(
+    config: &ChallengeConfig,
+    selectors: &[String],
+) -> Result<Vec<(ChallengeCommand, ChallengeCase)>, ChallengeCaseError> {
+    let cases = config.enumerate_cases()?;
+    let mut selected = vec![false; cases.len()];
+
+    for selector in selectors {
+        let matcher = glob(selector.trim_matches('/'))
+            .map_err(|source| ChallengeCaseError::InvalidSelector {
+                selector: selector.clone(),
+                source,
+            })?
+            .compile_matcher();
+        let mut matched_any = false;
+        for (i, (_, case)) in cases.iter().enumerate() {
+            if selects(&matcher, case) {
+                selected[i] = true;
+                matched_any = true;
+            }
+        }
+        if !matched_any {
+            let (searched, segment, names) = closest_names(config, selector);
+            let names = || names.iter().map(String::as_str);
+            return Err(ChallengeCaseError::SelectorMatchedNothing {
+                selector: selector.clone(),
+                searched,
+                suggestions: did_you_mean(segment, names()),
+                available: listable(names()),
+            });
+        }
+    }
+
+    Ok(cases
+        .into_iter()
+        .zip(selected)
+        .filter_map(|(case, selected)| selected.then_some(case))
+        .collect())
+}
+
+fn glob(pattern: &str) -> Result<Glob, globset::Error> {
+    GlobBuilder::new(pattern).literal_separator(true).build()
+}
+
+fn selects(matcher: &GlobMatcher, case: &ChallengeCase) -> bool {
+    let path = case
+        .parent_path()
+        .iter()
+        .chain([&case.name])
+        .cloned()
+        .collect::<Vec<_>>();
+    (1..=path.len()).any(|len| matcher.is_match(path[..len].join("/")))
+}
+
+/// The names available under the deepest group or part that the start of
+/// `selector` still matches, along with how that node was found and the
+/// segment of `selector` that matched none of the names.
+fn closest_names<'a>(
+    config: &ChallengeConfig,
+    selector: &'a str,
+) -> (String, &'a str, Vec<String>) {
+    let mut nodes = Vec::new();
+    collect_nodes(config, Vec::new(), &mut nodes);
+
+    let segments = selector.trim_matches('/').split('/').collect::<Vec<_>>();
+    for depth in (1..segments.len()).rev() {
+        let prefix = segments[..depth].join("/");
+        let Ok(prefix_glob) = glob(&prefix) else {
+            continue;
+        };
+        let matcher = prefix_glob.compile_matcher();
+        let mut available = Vec::new();
+        for (path, children) in &nodes {
+            if path.len() == depth && matcher.is_match(path.join("/")) {
+                for child in children {
+                    if !available.contains(child) {
+                        available.push(child.clone());
+                    }
+                }
+            }
+        }
+        if !available.is_empty() {
+            return (prefix, segments[depth], available);
+        }
+    }
+
+    let root_children = nodes.swap_remove(0).1;
+    (config.get_name().to_owned(), segments[0], root_children)
+}
+
+/// Every group and part in the tree, with the names directly under it.
+fn collect_nodes(
+    config: &ChallengeConfig,
+    path: Vec<String>,
+    nodes: &mut Vec<(Vec<String>, Vec<String>)>,
+) {
+    match config {
+        ChallengeConfig::Group(group) => {
+            let children = group.parts().iter().map(|p| p.get_name().to_owned());
+            nodes.push((path.clone(), children.collect()));
+            for part in group.parts() {
+                let mut part_path = path.clone();
+                part_path.push(part.get_name().to_owned());
+                collect_nodes(part, part_path, nodes);
+            }
+        }
+        ChallengeConfig::Part(part) => {
+            let children = part.cases().iter().map(|c| c.name.clone());
+            nodes.push((path, children.collect()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_selector_syntax() {
+        assert!(!is_selector_syntax(&[
+            "day03".to_owned(),
+            "part2".to_owned()
+        ]));
+        assert!(is_selector_syntax(&["day03/part2".to_owned()]));
+        assert!(is_selector_syntax(&["day*".to_owned()]));
+        assert!(is_selector_syntax(&["{part1,part2}".to_owned()]));
+    }
+}
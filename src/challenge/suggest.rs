@@ -0,0 +1,64 @@
+/// Available names are listed in errors only if there are at most this many.
+const MAX_LISTED_NAMES: usize = 8;
+
+/// Names from `candidates` that `name` is probably a typo of, closest first.
+pub fn did_you_mean<'a, I>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (name.chars().count() / 3).max(1);
+    let mut close = candidates
+        .into_iter()
+        .map(|candidate| {
+            let distance = if candidate.eq_ignore_ascii_case(name) {
+                0
+            } else {
+                strsim::damerau_levenshtein(name, candidate)
+            };
+            (distance, candidate)
+        })
+        .filter(|&(distance, _)| distance <= max_distance)
+        .collect::<Vec<_>>();
+    close.sort_by_key(|&(distance, _)| distance);
+    close
+        .into_iter()
+        .map(|(_, candidate)| candidate.to_owned())
+        .collect()
+}
+
+/// All of `candidates`, if there are few enough to list in an error.
+pub fn listable<'a, I>(candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let names = candidates
+        .into_iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if names.len() <= MAX_LISTED_NAMES {
+        names
+    } else {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_did_you_mean() {
+        let names = ["day01", "day02", "day10", "sample"];
+
+        assert_eq!(did_you_mean("day1", names), ["day01", "day10"]);
+        assert_eq!(did_you_mean("day03", names), ["day01", "day02"]);
+        assert_eq!(did_you_mean("Sample", names), ["sample"]);
+        assert!(did_you_mean("real", names).is_empty());
+    }
+
+    #[test]
+    fn test_listable() {
+        assert_eq!(listable(["a", "b"]), ["a", "b"]);
+        assert!(listable(["a"; 20]).is_empty());
+    }
+}
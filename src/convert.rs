@@ -0,0 +1,40 @@
+//! Writing challenge files out in another format.
+
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use crate::challenge::{ChallengeConfig, ChallengeParseError, ConfigFormat};
+use crate::errors::{ChallengeFileError, ConvertError, ProgramError};
+use crate::find_challenge_file;
+
+/// Write the challenge file for `challenge_path` to `output`, in the format
+/// `output`'s extension calls for. Comments aren't carried over. Returns the
+/// path of the file that was converted.
+pub fn convert_challenge<P: AsRef<Path>, Q: AsRef<Path>>(
+    challenge_path: P,
+    output: Q,
+) -> Result<PathBuf, ProgramError> {
+    let output = output.as_ref();
+    let to = ConfigFormat::from_extension(output)
+        .ok_or_else(|| ConvertError::UnknownFormat(output.to_owned()))?;
+    if output.exists() {
+        return Err(ConvertError::AlreadyExists(output.to_owned()).into());
+    }
+    let (_, challenge_file) = find_challenge_file(challenge_path)?;
+    let source = read_to_string(&challenge_file).map_err(ChallengeFileError::CouldNotOpenFile)?;
+    let from = ConfigFormat::of_file(&challenge_file);
+
+    let parse_err = |err: ChallengeParseError| err.in_file(&challenge_file);
+    ChallengeConfig::validate_str(&source, from).map_err(parse_err)?;
+    let document = from
+        .parse(&source)
+        .map_err(|diagnostic| parse_err(diagnostic.into()))?;
+    let converted = to
+        .to_string(&document)
+        .map_err(|source| ConvertError::CouldNotFormat { format: to, source })?;
+    ChallengeConfig::validate_str(&converted, to).map_err(ConvertError::InvalidResult)?;
+
+    write(output, converted)
+        .map_err(|err| ConvertError::CouldNotWriteFile(output.to_owned(), err))?;
+    Ok(challenge_file)
+}
@@ -2,7 +2,17 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
-use crate::challenge::{ChallengeCaseError, ChallengeExecutionError, ChallengeParseError};
+use crate::challenge::{
+    ChallengeCaseError, ChallengeExecutionError, ChallengeParseError, ConfigEditError, ConfigFormat,
+};
+use crate::yaml_edit::YamlEditError;
+
+/// Process exit code when a case ran but failed.
+pub const EXIT_TEST_FAILURE: u8 = 1;
+/// Process exit code when the challenge file or a case is invalid.
+pub const EXIT_CONFIG_ERROR: u8 = 2;
+/// Process exit code when the challenge file couldn't be found or opened.
+pub const EXIT_FILE_ERROR: u8 = 3;
 
 #[derive(Debug, Error)]
 pub enum ChallengeFileError {
@@ -10,8 +20,14 @@ pub enum ChallengeFileError {
     FileDoesNotExist(PathBuf),
     #[error("Could not find parent directory of '{0}'")]
     CouldNotFindParent(PathBuf),
-    #[error("Could not find challenge.yml or challenge.yaml in '{0}'")]
+    #[error("Could not find a challenge file (challenge.yml, .yaml, .toml or .json) in '{0}'")]
     FileNotFoundInDirectory(PathBuf),
+    #[error("Could not find a challenge file in '{0}' or any parent directory")]
+    NotFoundInAncestors(PathBuf),
+    #[error("Workspace '{0}' is not a directory")]
+    NotADirectory(PathBuf),
+    #[error("'{0}' is not a challenge file of its workspace")]
+    NotAWorkspaceMember(PathBuf),
 
     #[error(transparent)]
     CouldNotOpenFile(std::io::Error),
@@ -19,6 +35,70 @@ pub enum ChallengeFileError {
     CouldNotReadDirectory(std::io::Error),
 }
 
+#[derive(Debug, Error)]
+pub enum BlessError {
+    #[error("Could not read challenge file: {0}")]
+    CouldNotReadFile(std::io::Error),
+    #[error("Could not write '{0}': {1}")]
+    CouldNotWriteFile(PathBuf, std::io::Error),
+    #[error("Could not update expectation of case '{case}': {source}")]
+    CouldNotEditConfig {
+        case: String,
+        source: ConfigEditError,
+    },
+    #[error("Updating the expectations would produce an invalid challenge file: {0}")]
+    InvalidResult(ChallengeParseError),
+}
+
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    #[error("A challenge file already exists at '{0}'")]
+    AlreadyExists(PathBuf),
+    #[error("Could not read challenge file: {0}")]
+    CouldNotReadFile(std::io::Error),
+    #[error("Could not write '{0}': {1}")]
+    CouldNotWriteFile(PathBuf, std::io::Error),
+    #[error("Expected a path like 'group/part' or 'part/case'")]
+    EmptyPath,
+    #[error("'{0}' already exists")]
+    EntryExists(String),
+    #[error("Can't add parts to '{0}', which already has cases")]
+    HasCases(String),
+    #[error("Can't add cases to '{0}', which already has parts")]
+    HasParts(String),
+    #[error("'{0}' is included from another file. Add to that file instead")]
+    Included(String),
+    #[error("Could not edit challenge file: {0}")]
+    CouldNotEditConfig(#[from] ConfigEditError),
+    #[error("Scaffolding would produce an invalid challenge file: {0}")]
+    InvalidResult(ChallengeParseError),
+}
+
+#[derive(Debug, Error)]
+pub enum ConvertError {
+    #[error(
+        "Can't tell which format to write '{0}' in. Expected a .yml, .yaml, .toml or .json file"
+    )]
+    UnknownFormat(PathBuf),
+    #[error("'{0}' already exists")]
+    AlreadyExists(PathBuf),
+    #[error("Could not write '{0}': {1}")]
+    CouldNotWriteFile(PathBuf, std::io::Error),
+    #[error("Could not write the challenge as {format}: {source}")]
+    CouldNotFormat {
+        format: ConfigFormat,
+        source: ConfigEditError,
+    },
+    #[error("Converting would produce an invalid challenge file: {0}")]
+    InvalidResult(ChallengeParseError),
+}
+
+impl From<YamlEditError> for ScaffoldError {
+    fn from(err: YamlEditError) -> Self {
+        ScaffoldError::CouldNotEditConfig(err.into())
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ProgramError {
     #[error(transparent)]
@@ -29,4 +109,25 @@ pub enum ProgramError {
     InputCaseError(#[from] ChallengeCaseError),
     #[error(transparent)]
     ExecutionError(#[from] ChallengeExecutionError),
+    #[error(transparent)]
+    BlessError(#[from] BlessError),
+    #[error(transparent)]
+    ScaffoldError(#[from] ScaffoldError),
+    #[error(transparent)]
+    ConvertError(#[from] ConvertError),
+}
+impl ProgramError {
+    /// Process exit code to report this error with.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            ProgramError::InputFileError(_) => EXIT_FILE_ERROR,
+            ProgramError::ExecutionError(err) if err.is_test_failure() => EXIT_TEST_FAILURE,
+            ProgramError::ParseError(_)
+            | ProgramError::InputCaseError(_)
+            | ProgramError::ExecutionError(_)
+            | ProgramError::BlessError(_)
+            | ProgramError::ScaffoldError(_)
+            | ProgramError::ConvertError(_) => EXIT_CONFIG_ERROR,
+        }
+    }
 }
@@ -1,14 +1,203 @@
-use std::fs::{read_dir, File};
+use std::fs::read_to_string;
 use std::path::{Path, PathBuf};
 
+mod bless;
 pub mod challenge;
+mod convert;
 pub mod errors;
+mod list;
+mod runner;
+mod scaffold;
 mod template;
+mod yaml_edit;
 
-pub use self::errors::{ChallengeFileError, ProgramError};
-use crate::challenge::{ChallengeConfig, CommandConfig};
+pub use self::bless::{bless_challenge, bless_challenges, BlessOutcome, BlessedCase};
+pub use self::convert::convert_challenge;
+pub use self::errors::{
+    BlessError, ChallengeFileError, ConvertError, ProgramError, ScaffoldError, EXIT_CONFIG_ERROR,
+    EXIT_FILE_ERROR, EXIT_TEST_FAILURE,
+};
+pub use self::list::{list_challenge, ListedCase, ListedExpectation, ListedNode, ListedSource};
+pub use self::scaffold::{init_challenge, new_case, new_part, NewCaseOptions, Scaffolded};
+pub use self::yaml_edit::YamlEditError;
+use crate::challenge::{
+    CaseOutcome, ChallengeCase, ChallengeCommand, ChallengeConfig, ConfigDiagnostic, ConfigFormat,
+    MatcherRegistry, RunReport, StderrSink, CHALLENGE_FILE_NAMES, WORKSPACE_FILE_NAMES,
+};
+use crate::runner::run_cases;
 
-fn get_challenge_file<P: AsRef<Path>>(input: P) -> Result<(PathBuf, File), ChallengeFileError> {
+/// What to run: a challenge file, a directory holding one, or a workspace of
+/// challenge directories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChallengeTarget {
+    /// A challenge file or a directory with one, unless the directory has a
+    /// workspace file.
+    Path(PathBuf),
+    /// Every challenge file under a directory, whether or not it has a
+    /// workspace file.
+    Workspace(PathBuf),
+    /// A challenge file of the workspace in `workspace`, run on its own but
+    /// with what it inherits from the workspace file.
+    Member { workspace: PathBuf, file: PathBuf },
+}
+impl ChallengeTarget {
+    /// The file or directory this target names.
+    pub fn path(&self) -> &Path {
+        match self {
+            ChallengeTarget::Path(path) | ChallengeTarget::Workspace(path) => path,
+            ChallengeTarget::Member { file, .. } => file,
+        }
+    }
+}
+macro_rules! target_from_path {
+    ($($path:ty),+) => {$(
+        impl From<$path> for ChallengeTarget {
+            fn from(path: $path) -> Self {
+                ChallengeTarget::Path(PathBuf::from(path))
+            }
+        }
+    )+};
+}
+target_from_path!(&str, String, &String, &Path, PathBuf, &PathBuf);
+
+/// Where a target's challenge is defined.
+enum Found {
+    File {
+        dir: PathBuf,
+        file: PathBuf,
+    },
+    Workspace {
+        dir: PathBuf,
+        settings: Option<PathBuf>,
+    },
+    /// A challenge file of the workspace in `dir`. `member` is the path of
+    /// `file` relative to `dir`.
+    Member {
+        dir: PathBuf,
+        settings: Option<PathBuf>,
+        file: PathBuf,
+        member: PathBuf,
+    },
+}
+impl Found {
+    /// Directory the challenge runs in.
+    fn dir(&self) -> &Path {
+        match self {
+            Found::File { dir, .. } | Found::Workspace { dir, .. } | Found::Member { dir, .. } => {
+                dir
+            }
+        }
+    }
+
+    /// The challenge file, or the workspace file if there is one.
+    fn file(&self) -> Option<&Path> {
+        match self {
+            Found::File { file, .. } | Found::Member { file, .. } => Some(file),
+            Found::Workspace { settings, .. } => settings.as_deref(),
+        }
+    }
+}
+
+/// Locate the challenge file or workspace for `target`. Directories with a
+/// workspace file are workspaces, even if they also have a challenge file.
+fn find_challenge(target: ChallengeTarget) -> Result<Found, ChallengeFileError> {
+    let workspace_file = |dir: &Path| {
+        WORKSPACE_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|file| file.is_file())
+    };
+    match target {
+        ChallengeTarget::Workspace(dir) if dir.is_dir() => Ok(Found::Workspace {
+            settings: workspace_file(&dir),
+            dir,
+        }),
+        ChallengeTarget::Workspace(dir) => Err(ChallengeFileError::NotADirectory(dir)),
+        ChallengeTarget::Path(path) if path.is_dir() && workspace_file(&path).is_some() => {
+            Ok(Found::Workspace {
+                settings: workspace_file(&path),
+                dir: path,
+            })
+        }
+        ChallengeTarget::Path(path)
+            if path.is_file()
+                && path
+                    .file_name()
+                    .is_some_and(|name| WORKSPACE_FILE_NAMES.iter().any(|n| *n == name)) =>
+        {
+            let dir = path
+                .parent()
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            Ok(Found::Workspace {
+                dir: dir.to_owned(),
+                settings: Some(path),
+            })
+        }
+        ChallengeTarget::Path(path) => {
+            let (dir, file) = find_challenge_file(path)?;
+            Ok(Found::File { dir, file })
+        }
+        ChallengeTarget::Member { workspace, file } => {
+            let member = file
+                .strip_prefix(&workspace)
+                .map_err(|_| ChallengeFileError::NotAWorkspaceMember(file.clone()))?
+                .to_owned();
+            if !ChallengeConfig::is_workspace_member(&workspace, &member) {
+                return Err(ChallengeFileError::NotAWorkspaceMember(file));
+            }
+            Ok(Found::Member {
+                settings: workspace_file(&workspace),
+                dir: workspace,
+                file,
+                member,
+            })
+        }
+    }
+}
+
+/// Parse the challenge that `found` refers to.
+fn parse_found(found: &Found) -> Result<ChallengeConfig, ProgramError> {
+    let config = match found {
+        Found::File { dir, file } => {
+            let source = read_to_string(file).map_err(ChallengeFileError::CouldNotOpenFile)?;
+            ChallengeConfig::parse_str_as(&source, ConfigFormat::of_file(file), dir)
+        }
+        Found::Workspace { dir, settings } => {
+            let (source, format) = read_settings(settings.as_deref())?;
+            ChallengeConfig::parse_workspace(source.as_deref(), format, dir)
+        }
+        Found::Member {
+            dir,
+            settings,
+            member,
+            ..
+        } => {
+            let (source, format) = read_settings(settings.as_deref())?;
+            ChallengeConfig::parse_member(source.as_deref(), format, dir, member)
+        }
+    };
+    Ok(config.map_err(|err| match found.file() {
+        Some(file) => err.in_file(file),
+        None => err,
+    })?)
+}
+
+/// The contents of a workspace file, if there is one, and its format.
+fn read_settings(
+    settings: Option<&Path>,
+) -> Result<(Option<String>, ConfigFormat), ChallengeFileError> {
+    let source = settings
+        .map(read_to_string)
+        .transpose()
+        .map_err(ChallengeFileError::CouldNotOpenFile)?;
+    let format = settings.map(ConfigFormat::of_file).unwrap_or_default();
+    Ok((source, format))
+}
+
+/// Locate the challenge file for `input`, returning the directory the
+/// challenge runs in along with the path of the file itself.
+fn find_challenge_file<P: AsRef<Path>>(input: P) -> Result<(PathBuf, PathBuf), ChallengeFileError> {
     let input_path = input.as_ref();
     if input_path.is_file() {
         let parent = input_path
@@ -16,52 +205,229 @@ fn get_challenge_file<P: AsRef<Path>>(input: P) -> Result<(PathBuf, File), Chall
             .ok_or(ChallengeFileError::CouldNotFindParent(
                 input_path.to_owned(),
             ))?;
-        let file = File::open(input_path).map_err(ChallengeFileError::CouldNotOpenFile)?;
-        Ok((parent.to_owned(), file))
+        Ok((parent.to_owned(), input_path.to_owned()))
     } else if input_path.is_dir() {
-        let mut dir_reader =
-            read_dir(input_path).map_err(ChallengeFileError::CouldNotReadDirectory)?;
-        let challenge_file = dir_reader
-            .find_map(|res| {
-                if let Ok(ent) = res {
-                    if ent.file_name() == "challenge.yml" || ent.file_name() == "challenge.yaml" {
-                        return Some(ent.path());
-                    }
-                }
-                None
-            })
+        let challenge_file = CHALLENGE_FILE_NAMES
+            .iter()
+            .map(|name| input_path.join(name))
+            .find(|file| file.is_file())
             .ok_or_else(|| ChallengeFileError::FileNotFoundInDirectory(input_path.to_owned()))?;
-        let file = File::open(challenge_file).map_err(ChallengeFileError::CouldNotOpenFile)?;
 
-        Ok((input_path.to_owned(), file))
+        Ok((input_path.to_owned(), challenge_file))
     } else {
         Err(ChallengeFileError::FileDoesNotExist(input_path.to_owned()))
     }
 }
 
-pub fn run_challenge<P: AsRef<Path>>(
-    challenge_path: P,
+/// The challenge file nearest to `start`, in it or in one of its parent
+/// directories, the way git finds `.git`. A workspace file takes precedence
+/// over a challenge file next to it, like it does for [`ChallengeTarget::Path`].
+/// A challenge file that belongs to a workspace further up is a
+/// [`ChallengeTarget::Member`] of it.
+pub fn find_nearest_challenge<P: AsRef<Path>>(
+    start: P,
+) -> Result<ChallengeTarget, ChallengeFileError> {
+    let start = start.as_ref();
+    let find_in = |dir: &Path, names: &[&str]| {
+        names
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|file| file.is_file())
+    };
+    let (dir, file) = start
+        .ancestors()
+        .find_map(|dir| {
+            find_in(dir, &WORKSPACE_FILE_NAMES)
+                .or_else(|| find_in(dir, &CHALLENGE_FILE_NAMES))
+                .map(|file| (dir, file))
+        })
+        .ok_or_else(|| ChallengeFileError::NotFoundInAncestors(start.to_owned()))?;
+    if find_in(dir, &WORKSPACE_FILE_NAMES).is_some() {
+        return Ok(ChallengeTarget::Path(file));
+    }
+
+    let workspace = dir
+        .ancestors()
+        .skip(1)
+        .find(|ancestor| find_in(ancestor, &WORKSPACE_FILE_NAMES).is_some());
+    Ok(match workspace {
+        Some(workspace)
+            if file
+                .strip_prefix(workspace)
+                .is_ok_and(|member| ChallengeConfig::is_workspace_member(workspace, member)) =>
+        {
+            ChallengeTarget::Member {
+                workspace: workspace.to_owned(),
+                file,
+            }
+        }
+        _ => ChallengeTarget::Path(file),
+    })
+}
+
+/// Find and parse the challenge for `target`, returning the directory the
+/// challenge runs in along with its config.
+fn load_challenge<T: Into<ChallengeTarget>>(
+    target: T,
+) -> Result<(PathBuf, ChallengeConfig), ProgramError> {
+    let found = find_challenge(target.into())?;
+    let challenge_config = parse_found(&found)?;
+    Ok((found.dir().to_owned(), challenge_config))
+}
+
+/// Every problem in the challenge file for `target`, or in every file of a
+/// workspace, without running anything. An empty list means all is fine.
+pub fn check_challenge<T: Into<ChallengeTarget>>(
+    target: T,
+) -> Result<Vec<ConfigDiagnostic>, ProgramError> {
+    let found = find_challenge(target.into())?;
+    let source = found
+        .file()
+        .map(read_to_string)
+        .transpose()
+        .map_err(ChallengeFileError::CouldNotOpenFile)?;
+    let format = found.file().map(ConfigFormat::of_file).unwrap_or_default();
+    let diagnostics = match &found {
+        Found::File { dir, .. } => {
+            ChallengeConfig::check_str_as(source.as_deref().unwrap_or_default(), format, dir)
+        }
+        Found::Workspace { dir, .. } => {
+            ChallengeConfig::check_workspace(source.as_deref(), format, dir)
+        }
+        Found::Member {
+            dir,
+            settings,
+            member,
+            ..
+        } => {
+            let (source, format) = read_settings(settings.as_deref())?;
+            ChallengeConfig::check_member(source.as_deref(), format, dir, member)
+        }
+    };
+    Ok(diagnostics
+        .into_iter()
+        .map(|mut diagnostic| {
+            if let Some(file) = found.file() {
+                diagnostic.file.get_or_insert_with(|| file.to_owned());
+            }
+            diagnostic
+        })
+        .collect())
+}
+
+/// Run a single case, capturing all of its output.
+pub fn run_challenge<T: Into<ChallengeTarget>>(
+    target: T,
     cases: Vec<String>,
-) -> Result<(), ProgramError> {
-    let (challenge_dir, challenge_file) = get_challenge_file(challenge_path)?;
-    let challenge_config = ChallengeConfig::parse_file(challenge_file)?;
-    let (command, case) =
-        challenge_config.resolve_case(cases.into_iter(), CommandConfig::default())?;
-    case.execute(challenge_dir, &command)?;
+) -> Result<CaseOutcome, ProgramError> {
+    run_challenge_with(target, cases, &RunOptions::default())
+}
+/// Run a single case like [`run_challenge`], with the matchers and stderr
+/// sink in `options`.
+pub fn run_challenge_with<T: Into<ChallengeTarget>>(
+    target: T,
+    cases: Vec<String>,
+    options: &RunOptions,
+) -> Result<CaseOutcome, ProgramError> {
+    let (challenge_dir, challenge_config) = load_challenge(target)?;
+    let (command, case) = challenge_config.select_case(&cases)?;
+    let case = match &options.live_stderr {
+        Some(sink) => case.stream_stderr_to(sink.clone()),
+        None => case,
+    };
+
+    Ok(case.execute_with(challenge_dir, &command, &options.matchers))
+}
 
-    Ok(())
+/// A case that would have been run, and how.
+#[derive(Debug, Clone)]
+pub struct PlannedCase {
+    pub parent_path: Vec<String>,
+    pub name: String,
+    /// Shell line that runs the case exactly as a real run would.
+    pub shell_line: String,
 }
-pub fn run_challenges<P: AsRef<Path>>(
-    challenge_path: P,
+
+/// Resolve a single case like [`run_challenge`], without running it.
+pub fn dry_run_challenge<T: Into<ChallengeTarget>>(
+    target: T,
     cases: Vec<String>,
-) -> Result<(), ProgramError> {
-    let (challenge_dir, challenge_file) = get_challenge_file(challenge_path)?;
-    let challenge_config = ChallengeConfig::parse_file(challenge_file)?;
-    let cases = challenge_config.resolve_cases(cases.into_iter(), CommandConfig::default())?;
+) -> Result<PlannedCase, ProgramError> {
+    let (challenge_dir, challenge_config) = load_challenge(target)?;
+    let (command, case) = challenge_config.select_case(&cases)?;
 
-    for (cmd, case) in cases {
-        case.execute(&challenge_dir, &cmd)?;
+    plan_case(&challenge_dir, &command, case)
+}
+/// Resolve cases like [`run_challenges`], without running them.
+pub fn dry_run_challenges<T: Into<ChallengeTarget>>(
+    target: T,
+    cases: Vec<String>,
+) -> Result<Vec<PlannedCase>, ProgramError> {
+    let (challenge_dir, challenge_config) = load_challenge(target)?;
+    let cases = challenge_config.select_cases(&cases)?;
+
+    cases
+        .into_iter()
+        .map(|(command, case)| plan_case(&challenge_dir, &command, case))
+        .collect()
+}
+fn plan_case(
+    challenge_dir: &Path,
+    command: &ChallengeCommand,
+    case: ChallengeCase,
+) -> Result<PlannedCase, ProgramError> {
+    Ok(PlannedCase {
+        shell_line: case.shell_line(challenge_dir, command)?,
+        parent_path: case.parent_path().to_vec(),
+        name: case.name,
+    })
+}
+
+/// How [`run_challenges_with`] goes through the selected cases.
+#[derive(Debug, Clone)]
+pub struct RunOptions {
+    /// Skip the remaining cases once one has failed.
+    pub fail_fast: bool,
+    /// Maximum number of cases to run at the same time.
+    pub jobs: usize,
+    /// Matchers that expectations may name in `match`.
+    pub matchers: MatcherRegistry,
+    /// Where the stderr of cases in [`StderrMode::Live`] is copied as it's
+    /// written. Only used when cases run one at a time; otherwise it's just
+    /// captured.
+    pub live_stderr: Option<StderrSink>,
+}
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            fail_fast: false,
+            jobs: std::thread::available_parallelism().map_or(1, usize::from),
+            matchers: MatcherRegistry::default(),
+            live_stderr: None,
+        }
     }
+}
+
+pub fn run_challenges<T: Into<ChallengeTarget>>(
+    target: T,
+    cases: Vec<String>,
+) -> Result<RunReport, ProgramError> {
+    run_challenges_with(target, cases, &RunOptions::default(), |_| {})
+}
+/// Run the selected cases, handing each outcome to `on_outcome` in config
+/// order as soon as it and every case before it have finished.
+pub fn run_challenges_with<T, F>(
+    target: T,
+    cases: Vec<String>,
+    options: &RunOptions,
+    on_outcome: F,
+) -> Result<RunReport, ProgramError>
+where
+    T: Into<ChallengeTarget>,
+    F: FnMut(&CaseOutcome),
+{
+    let (challenge_dir, challenge_config) = load_challenge(target)?;
+    let cases = challenge_config.select_cases(&cases)?;
 
-    Ok(())
+    Ok(run_cases(challenge_dir, cases, options, on_outcome))
 }
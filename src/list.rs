@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::challenge::{
+    ChallengeCase, ChallengeConfig, ChallengeConfigPart, ChallengeExecutionError,
+    OutputExpectation, StringReference,
+};
+use crate::errors::ProgramError;
+use crate::{load_challenge, ChallengeTarget};
+
+/// A group or part of a challenge, with everything nested under it.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ListedNode {
+    Group {
+        name: String,
+        parts: Vec<ListedNode>,
+    },
+    Part {
+        name: String,
+        cases: Vec<ListedCase>,
+    },
+}
+impl ListedNode {
+    pub fn name(&self) -> &str {
+        match self {
+            ListedNode::Group { name, .. } | ListedNode::Part { name, .. } => name,
+        }
+    }
+}
+
+/// A case as it will be run, with inherited settings merged in.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedCase {
+    pub name: String,
+    /// Program and arguments after templating, the case's own arguments
+    /// included.
+    pub command: Vec<String>,
+    pub environment: BTreeMap<String, String>,
+    pub stdin: Option<ListedSource>,
+    pub expected: ListedExpectation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedExpectation {
+    pub stdout: Option<ListedSource>,
+    pub stderr: Option<ListedSource>,
+    pub exit_code: String,
+    pub checker: Option<Vec<String>>,
+}
+
+/// Where some input or expected output comes from.
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedSource {
+    #[serde(rename = "match", skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+impl From<&StringReference> for ListedSource {
+    fn from(reference: &StringReference) -> Self {
+        let (value, file) = match reference {
+            StringReference::Immediate(s) => (Some(s.clone()), None),
+            StringReference::File { file } => (None, Some(file.clone())),
+        };
+        Self {
+            matcher: None,
+            value,
+            file,
+        }
+    }
+}
+impl From<&OutputExpectation> for ListedSource {
+    fn from(expectation: &OutputExpectation) -> Self {
+        Self {
+            matcher: Some(expectation.matcher.clone()),
+            ..Self::from(&expectation.content)
+        }
+    }
+}
+
+/// Resolve the whole tree of a challenge, without running anything.
+pub fn list_challenge<T: Into<ChallengeTarget>>(target: T) -> Result<ListedNode, ProgramError> {
+    let (_, challenge_config) = load_challenge(target)?;
+
+    Ok(list_node(&challenge_config)?)
+}
+
+fn list_node(config: &ChallengeConfig) -> Result<ListedNode, ChallengeExecutionError> {
+    Ok(match config {
+        ChallengeConfig::Group(group) => ListedNode::Group {
+            name: group.name.clone(),
+            parts: group
+                .parts()
+                .iter()
+                .map(list_node)
+                .collect::<Result<_, _>>()?,
+        },
+        ChallengeConfig::Part(part) => ListedNode::Part {
+            name: part.name.clone(),
+            cases: part
+                .cases()
+                .iter()
+                .map(|case| list_case(case, part))
+                .collect::<Result<_, _>>()?,
+        },
+    })
+}
+
+fn list_case(
+    case: &ChallengeCase,
+    part: &ChallengeConfigPart,
+) -> Result<ListedCase, ChallengeExecutionError> {
+    let config = case.config();
+    let expected = case.expected().cloned().unwrap_or_default();
+    Ok(ListedCase {
+        name: case.name.clone(),
+        command: case.command_line(&part.command)?,
+        environment: config
+            .environment
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+        stdin: case.stdin().map(ListedSource::from),
+        expected: ListedExpectation {
+            stdout: expected.stdout.as_ref().map(ListedSource::from),
+            stderr: expected.stderr.as_ref().map(ListedSource::from),
+            exit_code: expected.exit_code.to_string(),
+            checker: case.checker_line()?,
+        },
+    })
+}
@@ -1,29 +1,509 @@
-use challenge_script::{run_challenge, run_challenges};
-use clap::Parser;
+use std::env::current_dir;
+use std::io::IsTerminal;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::process::ExitCode;
+
+use challenge_script::challenge::{
+    challenge_schema, CaseOutcome, CaseStatus, ChallengeExecutionError, RunReport, StderrMode,
+    StderrSink,
+};
+use challenge_script::{
+    bless_challenge, bless_challenges, check_challenge, convert_challenge, dry_run_challenge,
+    dry_run_challenges, find_nearest_challenge, init_challenge, list_challenge, new_case, new_part,
+    run_challenge_with, run_challenges_with, BlessOutcome, ChallengeFileError, ChallengeTarget,
+    ListedCase, ListedNode, ListedSource, NewCaseOptions, ProgramError, RunOptions,
+    EXIT_CONFIG_ERROR, EXIT_TEST_FAILURE,
+};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the parts and cases of a challenge as a tree
+    List(ListArgs),
+    /// Report every problem in a challenge file, without running anything
+    Check(CheckArgs),
+    /// Print the JSON schema of challenge files
+    Schema,
+    /// Create a challenge file with a single part and a sample case
+    Init(InitArgs),
+    /// Add a part or case to a challenge file
+    #[command(subcommand)]
+    New(NewCommand),
+    /// Write a challenge file in another format, picked by the output's extension
+    Convert(ConvertArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct InitArgs {
+    /// Directory to create challenge.yml in
+    #[arg(default_value = ".")]
+    dir: String,
+    /// Name of the challenge [default: the directory's name]
+    #[arg(long)]
+    name: Option<String>,
+    /// Command that runs the solution [default: ./solve {{part}}]
+    #[arg(long)]
+    command: Option<String>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ConvertArgs {
+    /// Path to the challenge folder or challenge file
+    challenge: String,
+    /// File to write, ending in .yml, .yaml, .toml or .json
+    output: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum NewCommand {
+    /// Add an empty part, like `day03/part2`, creating groups above it as needed
+    Part(NewPartArgs),
+    /// Add a case, like `day03/part2/sample`, with empty input and expected output
+    Case(NewCaseArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct NewPartArgs {
+    /// Slash-separated path of the new part
+    path: String,
+    /// Path to the challenge folder or challenge file
+    #[arg(short, long, default_value = ".")]
+    challenge: String,
+}
+
+#[derive(clap::Args, Debug)]
+struct NewCaseArgs {
+    /// Slash-separated path of the new case
+    path: String,
     /// Path to the challenge folder or challenge file
+    #[arg(short, long, default_value = ".")]
     challenge: String,
-    /// Challenge case (or nested parts and case) to run
+    /// Read stdin from an `input.txt` stub next to the challenge file.
+    #[arg(long)]
+    stdin_file: bool,
+    /// Compare stdout with an `expected.txt` stub next to the challenge file.
+    #[arg(long)]
+    expected_file: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct CheckArgs {
+    /// Path to the challenge folder or challenge file [default: the nearest
+    /// challenge file in this or a parent directory]
+    challenge: Option<String>,
+
+    /// Use every challenge file under the folder, as if it had a
+    /// `challenge-workspace.yml`.
+    #[arg(long)]
+    workspace: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct ListArgs {
+    /// Path to the challenge folder or challenge file [default: the nearest
+    /// challenge file in this or a parent directory]
+    challenge: Option<String>,
+
+    /// Use every challenge file under the folder, as if it had a
+    /// `challenge-workspace.yml`.
+    #[arg(long)]
+    workspace: bool,
+
+    /// Show each case's command, environment, input and expectations.
+    #[arg(short, long)]
+    verbose: bool,
+
+    #[arg(long, value_enum, default_value_t)]
+    format: ListFormat,
+}
+
+#[derive(ValueEnum, Debug, Default, Clone, Copy)]
+enum ListFormat {
+    #[default]
+    Text,
+    /// Every detail of every case, as JSON
+    Json,
+}
+
+#[derive(clap::Args, Debug)]
+struct RunArgs {
+    /// Path to the challenge folder or challenge file [default: the nearest
+    /// challenge file in this or a parent directory]. Taken as the first case
+    /// when no such path exists.
+    challenge: Option<String>,
+    /// Challenge case (or nested parts and case) to run, or path selectors
+    /// like `day03/part2/*` and `**/sample*`
     cases: Vec<String>,
 
     /// Run all nested parts and cases under the specified file and/or group.
     #[arg(short, long)]
     recursive: bool,
+
+    /// Stop running cases after the first failure.
+    #[arg(long)]
+    fail_fast: bool,
+
+    /// Number of cases to run at the same time [default: number of CPUs]
+    #[arg(short, long, value_name = "N")]
+    jobs: Option<NonZeroUsize>,
+
+    /// Record the actual output of every selected case as its expected output.
+    #[arg(long)]
+    bless: bool,
+
+    /// Print the shell line each selected case would run, without running it.
+    #[arg(long, conflicts_with = "bless")]
+    dry_run: bool,
+
+    /// Run every challenge file under the folder, as if it had a
+    /// `challenge-workspace.yml`.
+    #[arg(long)]
+    workspace: bool,
 }
 
-fn main() {
+fn main() -> ExitCode {
     let args = Args::parse();
 
-    let res = if args.recursive {
-        run_challenges(args.challenge, args.cases)
+    let res = match args.command {
+        Some(Command::List(list_args)) => list(list_args),
+        Some(Command::Check(check_args)) => check(check_args),
+        Some(Command::Schema) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&challenge_schema())
+                    .expect("schemas always serialize")
+            );
+            Ok(0)
+        }
+        Some(Command::Init(init_args)) => init(init_args),
+        Some(Command::New(new_command)) => new(new_command),
+        Some(Command::Convert(convert_args)) => convert(convert_args),
+        None => run(args.run),
+    };
+
+    match res {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn run(mut args: RunArgs) -> Result<u8, ProgramError> {
+    // Without a path, the first case lands in `challenge`; give it back when
+    // it doesn't name anything on disk.
+    if !args.workspace {
+        if let Some(case) = args.challenge.take_if(|path| !Path::new(path).exists()) {
+            args.cases.insert(0, case);
+        }
+    }
+    let challenge = target(args.challenge, args.workspace)?;
+    if args.dry_run {
+        let planned = if args.recursive {
+            dry_run_challenges(challenge, args.cases)?
+        } else {
+            vec![dry_run_challenge(challenge, args.cases)?]
+        };
+        for case in planned {
+            println!("# {}", case_name(&case.parent_path, &case.name));
+            println!("{}", case.shell_line);
+        }
+        Ok(0)
+    } else if args.bless {
+        let blessed = if args.recursive {
+            bless_challenges(challenge, args.cases)
+        } else {
+            bless_challenge(challenge, args.cases).map(|case| vec![case])
+        };
+        blessed.map(|blessed| {
+            for case in blessed {
+                let name = case_name(&case.parent_path, &case.name);
+                match case.outcome {
+                    BlessOutcome::WroteFile(file) => {
+                        println!("Blessed {name} into '{}'", file.display())
+                    }
+                    BlessOutcome::UpdatedConfig => println!("Blessed {name}"),
+                    BlessOutcome::Skipped(reason) => println!("Skipped {name}: {reason}"),
+                }
+            }
+            0
+        })
+    } else if args.recursive {
+        let mut options = RunOptions {
+            fail_fast: args.fail_fast,
+            live_stderr: Some(StderrSink::new(std::io::stderr())),
+            ..RunOptions::default()
+        };
+        if let Some(jobs) = args.jobs {
+            options.jobs = jobs.get();
+        }
+        run_challenges_with(challenge, args.cases, &options, print_outcome).map(|report| {
+            print_summary(&report);
+            if report.errored() > 0 {
+                EXIT_CONFIG_ERROR
+            } else if report.failed() > 0 {
+                EXIT_TEST_FAILURE
+            } else {
+                0
+            }
+        })
+    } else {
+        let options = RunOptions {
+            live_stderr: Some(StderrSink::new(std::io::stderr())),
+            ..RunOptions::default()
+        };
+        run_challenge_with(challenge, args.cases, &options).map(|outcome| {
+            print_outcome(&outcome);
+            match outcome.status {
+                CaseStatus::Failed(err) if !err.is_test_failure() => EXIT_CONFIG_ERROR,
+                CaseStatus::Failed(_) => EXIT_TEST_FAILURE,
+                _ => 0,
+            }
+        })
+    }
+}
+
+fn list(args: ListArgs) -> Result<u8, ProgramError> {
+    let tree = list_challenge(target(args.challenge, args.workspace)?)?;
+    match args.format {
+        ListFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(&tree).expect("listings always serialize")
+        ),
+        ListFormat::Text => {
+            println!("{}", tree.name());
+            print_children(&tree, "", args.verbose);
+        }
+    }
+    Ok(0)
+}
+
+fn init(args: InitArgs) -> Result<u8, ProgramError> {
+    let scaffolded = init_challenge(args.dir, args.name, args.command)?;
+    println!("Created '{}'", scaffolded.challenge_file.display());
+    Ok(0)
+}
+
+fn new(command: NewCommand) -> Result<u8, ProgramError> {
+    let (scaffolded, path) = match command {
+        NewCommand::Part(args) => (new_part(args.challenge, &args.path)?, args.path),
+        NewCommand::Case(args) => {
+            let options = NewCaseOptions {
+                stdin_file: args.stdin_file,
+                expected_file: args.expected_file,
+            };
+            (new_case(args.challenge, &args.path, options)?, args.path)
+        }
+    };
+    println!(
+        "Added '{path}' to '{}'",
+        scaffolded.challenge_file.display()
+    );
+    for file in scaffolded.created {
+        println!("Created '{}'", file.display());
+    }
+    Ok(0)
+}
+
+fn convert(args: ConvertArgs) -> Result<u8, ProgramError> {
+    let converted = convert_challenge(args.challenge, &args.output)?;
+    println!("Converted '{}' to '{}'", converted.display(), args.output);
+    Ok(0)
+}
+
+fn check(args: CheckArgs) -> Result<u8, ProgramError> {
+    let diagnostics = check_challenge(target(args.challenge, args.workspace)?)?;
+    for diagnostic in &diagnostics {
+        eprintln!("{diagnostic}\n");
+    }
+    match diagnostics.len() {
+        0 => {
+            println!("No problems found");
+            Ok(0)
+        }
+        1 => {
+            eprintln!("Found 1 problem");
+            Ok(EXIT_CONFIG_ERROR)
+        }
+        n => {
+            eprintln!("Found {n} problems");
+            Ok(EXIT_CONFIG_ERROR)
+        }
+    }
+}
+
+/// The challenge the command line refers to. Without a path, that's the
+/// current directory for workspaces, or else the nearest challenge file.
+fn target(challenge: Option<String>, workspace: bool) -> Result<ChallengeTarget, ProgramError> {
+    Ok(match challenge {
+        Some(challenge) if workspace => ChallengeTarget::Workspace(challenge.into()),
+        Some(challenge) => ChallengeTarget::Path(challenge.into()),
+        None if workspace => ChallengeTarget::Workspace(".".into()),
+        None => {
+            let current_dir = current_dir().map_err(ChallengeFileError::CouldNotReadDirectory)?;
+            let target = find_nearest_challenge(current_dir)?;
+            match &target {
+                ChallengeTarget::Member { workspace, file } => eprintln!(
+                    "Using '{}' from the workspace in '{}'",
+                    file.display(),
+                    workspace.display()
+                ),
+                target => eprintln!("Using '{}'", target.path().display()),
+            }
+            target
+        }
+    })
+}
+
+fn print_children(node: &ListedNode, prefix: &str, verbose: bool) {
+    match node {
+        ListedNode::Group { parts, .. } => {
+            for (i, part) in parts.iter().enumerate() {
+                let child_prefix = print_branch(prefix, part.name(), i + 1 == parts.len());
+                print_children(part, &child_prefix, verbose);
+            }
+        }
+        ListedNode::Part { cases, .. } => {
+            for (i, case) in cases.iter().enumerate() {
+                let child_prefix = print_branch(prefix, &case.name, i + 1 == cases.len());
+                if verbose {
+                    for line in case_details(case) {
+                        println!("{child_prefix}  {line}");
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Print one entry of the tree, returning the prefix for its children.
+fn print_branch(prefix: &str, name: &str, last: bool) -> String {
+    let (branch, indent) = if last {
+        ("└── ", "    ")
     } else {
-        run_challenge(args.challenge, args.cases)
+        ("├── ", "│   ")
+    };
+    println!("{prefix}{branch}{name}");
+    format!("{prefix}{indent}")
+}
+
+fn case_details(case: &ListedCase) -> Vec<String> {
+    let mut details = vec![format!("command: {}", shell_words(&case.command))];
+    if !case.environment.is_empty() {
+        let vars = case
+            .environment
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>();
+        details.push(format!("environment: {}", shell_words(&vars)));
+    }
+    if let Some(stdin) = &case.stdin {
+        details.push(format!("stdin: {}", describe_source(stdin)));
+    }
+    for (stream, source) in [
+        ("stdout", &case.expected.stdout),
+        ("stderr", &case.expected.stderr),
+    ] {
+        if let Some(source) = source {
+            details.push(format!("expected {stream}: {}", describe_source(source)));
+        }
+    }
+    details.push(format!("expected {}", case.expected.exit_code));
+    if let Some(checker) = &case.expected.checker {
+        details.push(format!("checker: {}", shell_words(checker)));
+    }
+    details
+}
+
+fn describe_source(source: &ListedSource) -> String {
+    let content = match (&source.value, &source.file) {
+        (_, Some(file)) => format!("file '{file}'"),
+        (Some(value), None) if value.trim().contains('\n') => {
+            format!("{} lines", value.trim().lines().count())
+        }
+        (Some(value), None) => format!("{:?}", value.trim()),
+        (None, None) => String::new(),
     };
+    match &source.matcher {
+        Some(matcher) => format!("{content} ({matcher})"),
+        None => content,
+    }
+}
+
+fn shell_words(words: &[String]) -> String {
+    shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" "))
+}
 
-    if let Err(err) = res {
-        eprintln!("{err}");
+fn print_outcome(outcome: &CaseOutcome) {
+    let name = case_name(&outcome.parent_path, &outcome.name);
+    if !matches!(outcome.status, CaseStatus::Skipped(_)) {
+        println!("{}", outcome.stdout);
+    }
+    if outcome.stderr_mode == StderrMode::Live && !outcome.stderr_streamed {
+        eprint!("{}", outcome.stderr);
+    }
+    match &outcome.status {
+        CaseStatus::Passed => println!("Passed {name} in {:.2?}", outcome.duration),
+        CaseStatus::Failed(err) => {
+            if outcome.stderr_mode == StderrMode::OnFailure {
+                eprint!("{}", outcome.stderr);
+            }
+            match err {
+                ChallengeExecutionError::UnexpectedOutput { stream, diff, .. } => {
+                    eprintln!("Failed {name}: wrong output on {stream}:");
+                    eprint!("{}", diff.render(use_color()));
+                }
+                // Stderr was already shown, unless it's captured.
+                ChallengeExecutionError::UnexpectedExitStatus {
+                    expected, status, ..
+                } if outcome.stderr_mode != StderrMode::Capture => {
+                    eprintln!("Failed {name}: Unexpected {status}, expected {expected}");
+                }
+                err => eprintln!("Failed {name}: {err}"),
+            }
+        }
+        CaseStatus::Skipped(reason) => println!("Skipped {name}: {reason}"),
     }
 }
+
+fn print_summary(report: &RunReport) {
+    println!(
+        "{} passed, {} failed, {} errors, {} skipped",
+        report.passed(),
+        report.failed(),
+        report.errored(),
+        report.skipped()
+    );
+}
+
+fn case_name(parent_path: &[String], name: &str) -> String {
+    let path = parent_path.join("/");
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}/{name}")
+    }
+}
+
+/// Whether to colour diffs. They're written to stderr, so that's what has to
+/// be a terminal: piping stdout elsewhere still leaves them coloured.
+fn use_color() -> bool {
+    std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal()
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use crate::challenge::{CaseOutcome, ChallengeCase, ChallengeCommand, RunReport, StderrSink};
+use crate::RunOptions;
+
+/// Cases run one after another by a single worker.
+type Unit = Vec<(usize, ChallengeCommand, ChallengeCase)>;
+
+/// Run `cases` on up to `options.jobs` workers. Outcomes are handed to
+/// `on_outcome` and collected in the order of `cases`, whichever finishes first.
+pub fn run_cases<P, F>(
+    challenge_dir: P,
+    cases: Vec<(ChallengeCommand, ChallengeCase)>,
+    options: &RunOptions,
+    mut on_outcome: F,
+) -> RunReport
+where
+    P: AsRef<Path>,
+    F: FnMut(&CaseOutcome),
+{
+    let total = cases.len();
+    let jobs = options.jobs.clamp(1, total.max(1));
+    // Live stderr is only passed on when it can't be interleaved with the
+    // stderr of other cases.
+    let sink = options.live_stderr.clone().filter(|_| jobs == 1);
+    let units = into_units(cases, sink);
+
+    let queue = Mutex::new(units.into_iter());
+    let failed = AtomicBool::new(false);
+    let (sender, receiver) = mpsc::channel();
+    let challenge_dir = challenge_dir.as_ref();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (queue, failed) = (&queue, &failed);
+            scope.spawn(move || {
+                while let Some(unit) = queue.lock().ok().and_then(|mut q| q.next()) {
+                    for (index, command, case) in unit {
+                        let outcome = if options.fail_fast && failed.load(Ordering::Relaxed) {
+                            let reason = "an earlier case failed".to_owned();
+                            CaseOutcome::skipped(case.parent_path().to_vec(), case.name, reason)
+                        } else {
+                            case.execute_with(challenge_dir, &command, &options.matchers)
+                        };
+                        if outcome.is_failed() {
+                            failed.store(true, Ordering::Relaxed);
+                        }
+                        if sender.send((index, outcome)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut pending = (0..total)
+            .map(|_| None)
+            .collect::<Vec<Option<CaseOutcome>>>();
+        let mut report = RunReport::default();
+        for (index, outcome) in receiver {
+            pending[index] = Some(outcome);
+            while let Some(outcome) = pending.get_mut(report.cases.len()).and_then(Option::take) {
+                on_outcome(&outcome);
+                report.cases.push(outcome);
+            }
+        }
+        report
+    })
+}
+
+/// Split cases into units of work, keeping cases of the same serial group
+/// together so they never run at the same time.
+fn into_units(
+    cases: Vec<(ChallengeCommand, ChallengeCase)>,
+    sink: Option<StderrSink>,
+) -> Vec<Unit> {
+    let mut units: Vec<Unit> = Vec::new();
+    let mut serial_units = HashMap::new();
+    for (index, (command, case)) in cases.into_iter().enumerate() {
+        let case = match &sink {
+            Some(sink) => case.stream_stderr_to(sink.clone()),
+            None => case,
+        };
+        match case.serial_group() {
+            Some(group) => {
+                let unit = *serial_units.entry(group.to_vec()).or_insert_with(|| {
+                    units.push(Vec::new());
+                    units.len() - 1
+                });
+                units[unit].push((index, command, case));
+            }
+            None => units.push(vec![(index, command, case)]),
+        }
+    }
+    units
+}
@@ -0,0 +1,292 @@
+//! Creating challenge files, and adding parts and cases to existing ones.
+
+use std::fs::{create_dir_all, read_to_string, write, File};
+use std::path::{Path, PathBuf};
+
+use serde_yaml::{Mapping, Value};
+
+use crate::bless::lookup;
+use crate::challenge::{challenge_schema, ChallengeConfig, ChallengeParseError, ConfigFormat};
+use crate::errors::{ProgramError, ScaffoldError};
+use crate::find_challenge_file;
+use crate::yaml_edit::{set_scalar, set_value};
+
+const CHALLENGE_FILE_NAME: &str = "challenge.yml";
+const DEFAULT_COMMAND: &str = "./solve {{part}}";
+const DEFAULT_PART: &str = "part1";
+const SAMPLE_CASE: &str = "sample";
+const STDIN_STUB: &str = "input.txt";
+const EXPECTED_STUB: &str = "expected.txt";
+
+/// Files written while scaffolding.
+#[derive(Debug, Clone)]
+pub struct Scaffolded {
+    pub challenge_file: PathBuf,
+    /// Stub files created next to the challenge file.
+    pub created: Vec<PathBuf>,
+}
+
+/// How [`new_case`] fills in the new case.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NewCaseOptions {
+    /// Read stdin from an `input.txt` stub instead of an inline string.
+    pub stdin_file: bool,
+    /// Compare stdout with an `expected.txt` stub instead of an inline string.
+    pub expected_file: bool,
+}
+
+/// Write a `challenge.yml` into `dir` with a single part and a sample case.
+/// The name defaults to the directory's name.
+pub fn init_challenge<P: AsRef<Path>>(
+    dir: P,
+    name: Option<String>,
+    command: Option<String>,
+) -> Result<Scaffolded, ProgramError> {
+    let dir = dir.as_ref();
+    if let Ok((_, existing)) = find_challenge_file(dir) {
+        return Err(ScaffoldError::AlreadyExists(existing).into());
+    }
+    let name = name.unwrap_or_else(|| default_name(dir));
+    let command = command.unwrap_or_else(|| DEFAULT_COMMAND.to_owned());
+
+    let mut source = set_scalar("", &["name"], &name).map_err(ScaffoldError::from)?;
+    source = set_scalar(&source, &["command"], &command).map_err(ScaffoldError::from)?;
+    source = set_value(
+        &source,
+        &["parts", DEFAULT_PART, "cases", SAMPLE_CASE],
+        &case_value(None, None),
+    )
+    .map_err(ScaffoldError::from)?;
+    if let Some(id) = challenge_schema().get("$id").and_then(|id| id.as_str()) {
+        source = format!("# yaml-language-server: $schema={id}\n{source}");
+    }
+    ChallengeConfig::parse_str(&source).map_err(ScaffoldError::InvalidResult)?;
+
+    create_dir_all(dir).map_err(|err| ScaffoldError::CouldNotWriteFile(dir.to_owned(), err))?;
+    let challenge_file = dir.join(CHALLENGE_FILE_NAME);
+    write(&challenge_file, source)
+        .map_err(|err| ScaffoldError::CouldNotWriteFile(challenge_file.clone(), err))?;
+    Ok(Scaffolded {
+        challenge_file,
+        created: Vec::new(),
+    })
+}
+
+/// Add an empty part at `part_path`, like `day03` or `day03/part2`, creating
+/// any groups above it that don't exist yet.
+pub fn new_part<P: AsRef<Path>>(
+    challenge_path: P,
+    part_path: &str,
+) -> Result<Scaffolded, ProgramError> {
+    let (challenge_dir, challenge_file, source, document) = read_challenge(challenge_path)?;
+    let format = ConfigFormat::of_file(&challenge_file);
+    let names = split_path(part_path)?;
+    check_parents(&document, &names, &names)?;
+
+    let mut keys = node_keys(&names);
+    keys.push("cases".to_owned());
+    let source = edit(
+        &source,
+        format,
+        &challenge_dir,
+        &keys,
+        &Value::Mapping(Mapping::new()),
+    )?;
+    write_challenge(&challenge_file, &source)?;
+    Ok(Scaffolded {
+        challenge_file,
+        created: Vec::new(),
+    })
+}
+
+/// Add a case at `case_path`, like `day03/sample`, with empty input and
+/// expected output. Parts and groups above it are created if needed.
+pub fn new_case<P: AsRef<Path>>(
+    challenge_path: P,
+    case_path: &str,
+    options: NewCaseOptions,
+) -> Result<Scaffolded, ProgramError> {
+    let (challenge_dir, challenge_file, source, document) = read_challenge(challenge_path)?;
+    let format = ConfigFormat::of_file(&challenge_file);
+    let names = split_path(case_path)?;
+    let (case_name, part_names) = names.split_last().expect("paths are never empty");
+    check_parents(&document, part_names, &names)?;
+    if let Some(part) = lookup(&document, &node_keys(part_names)) {
+        if part.get("include").is_some() {
+            return Err(ScaffoldError::Included(label(&document, part_names)).into());
+        }
+        if part.get("parts").is_some() {
+            return Err(ScaffoldError::HasParts(label(&document, part_names)).into());
+        }
+    }
+
+    let stub_dir = names.iter().collect::<PathBuf>();
+    let stub = |enabled: bool, file_name: &str| enabled.then(|| stub_dir.join(file_name));
+    let stdin_stub = stub(options.stdin_file, STDIN_STUB);
+    let expected_stub = stub(options.expected_file, EXPECTED_STUB);
+
+    let mut keys = node_keys(part_names);
+    keys.extend(["cases".to_owned(), case_name.clone()]);
+    let source = edit(
+        &source,
+        format,
+        &challenge_dir,
+        &keys,
+        &case_value(stdin_stub.as_deref(), expected_stub.as_deref()),
+    )?;
+
+    let mut created = Vec::new();
+    for stub in [stdin_stub, expected_stub].into_iter().flatten() {
+        let stub = challenge_dir.join(stub);
+        if stub.exists() {
+            continue;
+        }
+        if let Some(parent) = stub.parent() {
+            create_dir_all(parent)
+                .map_err(|err| ScaffoldError::CouldNotWriteFile(parent.to_owned(), err))?;
+        }
+        File::create(&stub).map_err(|err| ScaffoldError::CouldNotWriteFile(stub.clone(), err))?;
+        created.push(stub);
+    }
+    write_challenge(&challenge_file, &source)?;
+    Ok(Scaffolded {
+        challenge_file,
+        created,
+    })
+}
+
+fn default_name(dir: &Path) -> String {
+    std::path::absolute(dir)
+        .ok()
+        .and_then(|dir| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "Challenge".to_owned())
+}
+
+/// Find and read a challenge file, making sure it's valid before editing it.
+fn read_challenge<P: AsRef<Path>>(
+    challenge_path: P,
+) -> Result<(PathBuf, PathBuf, String, Value), ProgramError> {
+    let (challenge_dir, challenge_file) = find_challenge_file(challenge_path)?;
+    let source = read_to_string(&challenge_file).map_err(ScaffoldError::CouldNotReadFile)?;
+    let format = ConfigFormat::of_file(&challenge_file);
+    ChallengeConfig::parse_str_as(&source, format, &challenge_dir)
+        .map_err(|err| err.in_file(&challenge_file))?;
+    let document = format.parse(&source).map_err(ChallengeParseError::from)?;
+    Ok((challenge_dir, challenge_file, source, document))
+}
+
+fn split_path(path: &str) -> Result<Vec<String>, ScaffoldError> {
+    let names = path
+        .split('/')
+        .filter(|name| !name.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    if names.is_empty() {
+        return Err(ScaffoldError::EmptyPath);
+    }
+    Ok(names)
+}
+
+/// Keys leading to the group or part at `names`.
+fn node_keys(names: &[String]) -> Vec<String> {
+    names
+        .iter()
+        .flat_map(|name| ["parts".to_owned(), name.clone()])
+        .collect()
+}
+
+/// Make sure nothing exists at `new` yet, and that none of the nodes above
+/// it in `parents` already hold cases instead of parts, or come from another
+/// file.
+fn check_parents(
+    document: &Value,
+    parents: &[String],
+    new: &[String],
+) -> Result<(), ScaffoldError> {
+    for depth in 0..parents.len() {
+        let Some(node) = lookup(document, &node_keys(&parents[..depth])) else {
+            continue;
+        };
+        if node.get("include").is_some() {
+            return Err(ScaffoldError::Included(label(document, &parents[..depth])));
+        }
+        if node.get("cases").is_some() {
+            return Err(ScaffoldError::HasCases(label(document, &parents[..depth])));
+        }
+    }
+
+    let new_keys = match new.split_last() {
+        Some((case, part)) if part.len() == parents.len() => {
+            let mut keys = node_keys(part);
+            keys.extend(["cases".to_owned(), case.clone()]);
+            keys
+        }
+        _ => node_keys(new),
+    };
+    if lookup(document, &new_keys).is_some() {
+        return Err(ScaffoldError::EntryExists(new.join("/")));
+    }
+    Ok(())
+}
+
+/// How a node is referred to in errors: by its path, or by the challenge's
+/// name at the top level.
+fn label(document: &Value, names: &[String]) -> String {
+    if names.is_empty() {
+        document
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned()
+    } else {
+        names.join("/")
+    }
+}
+
+fn case_value(stdin_file: Option<&Path>, expected_file: Option<&Path>) -> Value {
+    let reference = |file: Option<&Path>| match file {
+        Some(file) => {
+            let mut reference = Mapping::new();
+            reference.insert("file".into(), file_reference(file).into());
+            Value::Mapping(reference)
+        }
+        None => Value::String(String::new()),
+    };
+    let mut expected = Mapping::new();
+    expected.insert("stdout".into(), reference(expected_file));
+
+    let mut case = Mapping::new();
+    case.insert("stdin".into(), reference(stdin_file));
+    case.insert("expected".into(), Value::Mapping(expected));
+    Value::Mapping(case)
+}
+
+/// Challenge files refer to files with forward slashes on every platform.
+fn file_reference(file: &Path) -> String {
+    file.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn edit(
+    source: &str,
+    format: ConfigFormat,
+    challenge_dir: &Path,
+    keys: &[String],
+    value: &Value,
+) -> Result<String, ScaffoldError> {
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    let source = format.set_value(source, &keys, value)?;
+    ChallengeConfig::parse_str_as(&source, format, challenge_dir)
+        .map_err(ScaffoldError::InvalidResult)?;
+    Ok(source)
+}
+
+fn write_challenge(challenge_file: &Path, source: &str) -> Result<(), ScaffoldError> {
+    write(challenge_file, source)
+        .map_err(|err| ScaffoldError::CouldNotWriteFile(challenge_file.to_owned(), err))
+}
@@ -1,19 +1,34 @@
 use std::collections::HashMap;
+use std::hash::Hash;
 
-pub fn template_string(template_str: &str, context: &HashMap<&str, &str>) -> String {
-    let mut ret = template_str.to_owned();
-
-    for (key, val) in context.iter() {
-        let pattern = format!("{{{{{key}}}}}");
-        let match_ranges = ret
-            .match_indices(&pattern)
-            .map(|(i, m)| i..(i + m.len()))
-            .collect::<Vec<_>>();
-        for range in match_ranges {
-            ret.replace_range(range, val);
+/// Fill in every `{{key}}` in `template_str` that has a value in `context`.
+/// Filled-in values aren't templated again, and unknown keys are left as is.
+pub fn template_string<K, V>(template_str: &str, context: &HashMap<K, V>) -> String
+where
+    K: std::borrow::Borrow<str> + Eq + Hash,
+    V: AsRef<str>,
+{
+    let mut ret = String::with_capacity(template_str.len());
+    let mut rest = template_str;
+    while let Some(start) = rest.find("{{") {
+        ret.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest[2..]
+            .find("}}")
+            .and_then(|len| Some((context.get(&rest[2..2 + len])?, len)));
+        match value {
+            Some((value, len)) => {
+                ret.push_str(value.as_ref());
+                rest = &rest[len + 4..];
+            }
+            // A key may still start further in, like in `{{{key}}}`.
+            None => {
+                ret.push('{');
+                rest = &rest[1..];
+            }
         }
     }
-
+    ret.push_str(rest);
     ret
 }
 
@@ -49,4 +64,22 @@ mod test {
 
         assert_eq!(result, "Hello? {{c}}!");
     }
+
+    #[test]
+    fn test_keys_inside_braces() {
+        let context = HashMap::from([("a", "Hello")]);
+
+        assert_eq!(template_string("{{{a}}}", &context), "{Hello}");
+        assert_eq!(template_string("{{ {{a}}", &context), "{{ Hello");
+        assert_eq!(template_string("{{b}} {{a}}", &context), "{{b}} Hello");
+    }
+
+    #[test]
+    fn test_does_not_template_values() {
+        let context = HashMap::from([("a", "{{b}}"), ("b", "World"), ("long", "x")]);
+
+        let result = template_string("{{a}}, {{long}}{{long}}, {{b}}", &context);
+
+        assert_eq!(result, "{{b}}, xx, World");
+    }
 }
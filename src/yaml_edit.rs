@@ -0,0 +1,452 @@
+//! Minimal line-based editing of block-style YAML documents, which keeps
+//! comments, key order and formatting of everything it doesn't touch.
+
+use std::ops::Range;
+
+use serde_yaml::Value;
+use thiserror::Error;
+
+const DEFAULT_INDENT_STEP: usize = 2;
+
+#[derive(Debug, Error)]
+pub enum YamlEditError {
+    #[error("Can't edit '{0}' because it is written in flow style")]
+    FlowStyle(String),
+    #[error("Can't edit an empty key path")]
+    EmptyPath,
+    #[error("Couldn't format value as YAML: {0}")]
+    Format(#[from] serde_yaml::Error),
+}
+
+/// Set the scalar at `path` to `value`, replacing whatever was there and
+/// creating any missing mappings along the way.
+pub fn set_scalar(source: &str, path: &[&str], value: &str) -> Result<String, YamlEditError> {
+    set_value(source, path, &Value::String(value.to_owned()))
+}
+
+/// Like [`set_scalar`], for any value. Mappings and sequences are written in
+/// block style.
+pub fn set_value(source: &str, path: &[&str], value: &Value) -> Result<String, YamlEditError> {
+    let (last, parents) = path.split_last().ok_or(YamlEditError::EmptyPath)?;
+    let mut lines = source.lines().map(str::to_owned).collect::<Vec<_>>();
+    let indent_step = detect_indent_step(&lines);
+
+    let mut range = 0..lines.len();
+    let mut indent = first_content_line(&lines, range.clone())
+        .map(|i| indent_of(&lines[i]))
+        .unwrap_or(0);
+    for (depth, key) in parents.iter().enumerate() {
+        let Some(key_line) = find_key(&lines, range.clone(), indent, key) else {
+            let mut new_lines = Vec::new();
+            for (i, missing) in parents[depth..].iter().enumerate() {
+                new_lines.push(format!(
+                    "{}{}:",
+                    " ".repeat(indent + i * indent_step),
+                    format_key(missing)?
+                ));
+            }
+            let value_indent = indent + (parents.len() - depth) * indent_step;
+            new_lines.extend(render_entry(last, value, value_indent, indent_step)?);
+            let at = insertion_point(&lines, range);
+            lines.splice(at..at, new_lines);
+            return Ok(join_lines(lines, source));
+        };
+
+        match inline_value(&lines[key_line]) {
+            None => {}
+            Some("{}") => {
+                let colon = lines[key_line].rfind("{}").unwrap_or(lines[key_line].len());
+                lines[key_line].truncate(colon);
+                let trimmed_len = lines[key_line].trim_end().len();
+                lines[key_line].truncate(trimmed_len);
+            }
+            Some(_) => return Err(YamlEditError::FlowStyle(path[..=depth].join("."))),
+        }
+
+        let block_end = block_end(&lines, key_line);
+        range = (key_line + 1)..block_end;
+        indent = first_content_line(&lines, range.clone())
+            .map(|i| indent_of(&lines[i]))
+            .unwrap_or(indent + indent_step);
+    }
+
+    let mut new_lines = render_entry(last, value, indent, indent_step)?;
+    match find_key(&lines, range.clone(), indent, last) {
+        Some(key_line) => {
+            if let Some(comment) = trailing_comment(&lines[key_line]) {
+                new_lines[0] = format!("{} {comment}", new_lines[0]);
+            }
+            let end = block_end(&lines, key_line);
+            lines.splice(key_line..end, new_lines);
+        }
+        None => {
+            let at = insertion_point(&lines, range);
+            lines.splice(at..at, new_lines);
+        }
+    }
+
+    Ok(join_lines(lines, source))
+}
+
+/// Find the entry at `path`, or the deepest entry along it that exists.
+/// Returns its 0-based line, the column its key starts at and the key's length.
+pub fn locate(source: &str, path: &[&str]) -> Option<(usize, usize, usize)> {
+    let lines = source.lines().map(str::to_owned).collect::<Vec<_>>();
+    let mut range = 0..lines.len();
+    let mut found = None;
+    for key in path {
+        let indent = first_content_line(&lines, range.clone()).map(|i| indent_of(&lines[i]))?;
+        let Some(key_line) = find_key(&lines, range.clone(), indent, key) else {
+            break;
+        };
+        let key_len = lines[key_line].trim_start().find(':').unwrap_or(key.len());
+        found = Some((key_line, indent, key_len));
+        if inline_value(&lines[key_line]).is_some() {
+            break;
+        }
+        range = (key_line + 1)..block_end(&lines, key_line);
+    }
+    found
+}
+
+fn join_lines(lines: Vec<String>, original: &str) -> String {
+    let mut out = lines.join("\n");
+    if original.ends_with('\n') || original.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+fn is_content(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    !trimmed.is_empty() && !trimmed.starts_with('#')
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+fn first_content_line(lines: &[String], range: Range<usize>) -> Option<usize> {
+    range.into_iter().find(|&i| is_content(&lines[i]))
+}
+
+fn detect_indent_step(lines: &[String]) -> usize {
+    let mut previous = 0;
+    for line in lines.iter().filter(|l| is_content(l)) {
+        let indent = indent_of(line);
+        if indent > previous {
+            return indent - previous;
+        }
+        previous = indent;
+    }
+    DEFAULT_INDENT_STEP
+}
+
+/// Split a mapping entry line into its unquoted key and the text after the colon.
+fn split_entry(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_start();
+    let (key, rest) = match trimmed.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let end = trimmed[1..].find(quote)? + 1;
+            (trimmed[1..end].to_owned(), &trimmed[end + 1..])
+        }
+        _ => {
+            let colon = trimmed.find(": ").or_else(|| {
+                trimmed
+                    .strip_suffix(':')
+                    .map(|without_colon| without_colon.len())
+            })?;
+            (trimmed[..colon].trim_end().to_owned(), &trimmed[colon..])
+        }
+    };
+    let rest = rest.trim_start().strip_prefix(':')?;
+    Some((key, rest))
+}
+
+fn find_key(lines: &[String], range: Range<usize>, indent: usize, key: &str) -> Option<usize> {
+    range.into_iter().find(|&i| {
+        let line = &lines[i];
+        is_content(line)
+            && indent_of(line) == indent
+            && split_entry(line).is_some_and(|(k, _)| k == key)
+    })
+}
+
+/// The value written on the same line as its key, ignoring comments.
+fn inline_value(line: &str) -> Option<&str> {
+    let (_, rest) = split_entry(line)?;
+    let value = match rest.find(" #") {
+        Some(comment) => &rest[..comment],
+        None => rest,
+    }
+    .trim();
+    (!value.is_empty()).then_some(value)
+}
+
+/// The comment at the end of an entry line, like `# note` in `key: value # note`.
+fn trailing_comment(line: &str) -> Option<&str> {
+    let (_, rest) = split_entry(line)?;
+    let value = rest.trim_start();
+    if value.starts_with('#') {
+        return Some(value.trim_end());
+    }
+    // Skip over quoted values, which may contain ` #` themselves.
+    let after_value = match value.chars().next() {
+        Some(quote @ ('"' | '\'')) => &value[value[1..].find(quote)? + 2..],
+        _ => value,
+    };
+    let comment = after_value.find(" #")?;
+    Some(after_value[comment + 1..].trim_end())
+}
+
+/// The line after the last line belonging to the entry at `key_line`.
+fn block_end(lines: &[String], key_line: usize) -> usize {
+    let indent = indent_of(&lines[key_line]);
+    let mut end = key_line + 1;
+    for (i, line) in lines.iter().enumerate().skip(key_line + 1) {
+        if !is_content(line) {
+            continue;
+        }
+        let line_indent = indent_of(line);
+        let is_sequence_item = line_indent == indent && line.trim_start().starts_with("- ");
+        if line_indent <= indent && !is_sequence_item {
+            break;
+        }
+        end = i + 1;
+    }
+    end
+}
+
+/// Where to add a new entry at the end of `range`: after its last content line.
+fn insertion_point(lines: &[String], range: Range<usize>) -> usize {
+    range
+        .clone()
+        .rev()
+        .find(|&i| is_content(&lines[i]))
+        .map(|i| i + 1)
+        .unwrap_or(range.start)
+}
+
+fn format_key(key: &str) -> Result<String, YamlEditError> {
+    Ok(serde_yaml::to_string(key)?.trim_end().to_owned())
+}
+
+/// Render `key: value`, nesting collections below the key.
+fn render_entry(
+    key: &str,
+    value: &Value,
+    indent: usize,
+    indent_step: usize,
+) -> Result<Vec<String>, YamlEditError> {
+    if let Value::String(value) = value {
+        return render_string_entry(key, value, indent, indent_step);
+    }
+
+    let prefix = " ".repeat(indent);
+    let key = format_key(key)?;
+    let rendered = serde_yaml::to_string(value)?;
+    let is_empty_collection = matches!(value, Value::Mapping(m) if m.is_empty())
+        || matches!(value, Value::Sequence(s) if s.is_empty());
+    if !value.is_mapping() && !value.is_sequence() || is_empty_collection {
+        return Ok(vec![format!("{prefix}{key}: {}", rendered.trim_end())]);
+    }
+
+    let content_prefix = " ".repeat(indent + indent_step);
+    let mut lines = vec![format!("{prefix}{key}:")];
+    lines.extend(rendered.lines().map(|l| format!("{content_prefix}{l}")));
+    Ok(lines)
+}
+
+/// Render `key: value`, using a literal block scalar for multi-line values.
+/// Values with characters a block scalar can't hold, like `\r`, are written
+/// double-quoted on one line instead.
+fn render_string_entry(
+    key: &str,
+    value: &str,
+    indent: usize,
+    indent_step: usize,
+) -> Result<Vec<String>, YamlEditError> {
+    let prefix = " ".repeat(indent);
+    let key = format_key(key)?;
+    if !value.contains('\n') {
+        let scalar = serde_yaml::to_string(value)?;
+        return Ok(vec![format!("{prefix}{key}: {}", scalar.trim_end())]);
+    }
+    if value.contains(needs_escape) {
+        // JSON strings are valid double-quoted YAML scalars.
+        let scalar = serde_json::to_string(value).expect("strings always serialize");
+        return Ok(vec![format!("{prefix}{key}: {scalar}")]);
+    }
+
+    let content = value.trim_end_matches('\n');
+    let chomping = match value.len() - content.len() {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    };
+    // The indentation is otherwise taken from the first non-empty line.
+    let leading_space = content
+        .split('\n')
+        .find(|l| !l.is_empty())
+        .is_some_and(|l| l.starts_with([' ', '\t']));
+    let indicator = if leading_space {
+        indent_step.to_string()
+    } else {
+        String::new()
+    };
+    let content_prefix = " ".repeat(indent + indent_step);
+
+    let mut lines = vec![format!("{prefix}{key}: |{indicator}{chomping}")];
+    lines.extend(
+        value
+            .split('\n')
+            .take(value.split('\n').count() - 1)
+            .map(|l| {
+                if l.is_empty() {
+                    String::new()
+                } else {
+                    format!("{content_prefix}{l}")
+                }
+            }),
+    );
+    if chomping == "-" {
+        lines.push(format!(
+            "{content_prefix}{}",
+            content.rsplit('\n').next().unwrap_or("")
+        ));
+    }
+    Ok(lines)
+}
+
+/// Whether `c` can't appear as itself in a literal block scalar.
+fn needs_escape(c: char) -> bool {
+    (c.is_control() && !matches!(c, '\n' | '\t'))
+        || matches!(c, '\u{2028}' | '\u{2029}' | '\u{feff}')
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(source: &str) -> serde_yaml::Value {
+        serde_yaml::from_str(source).unwrap()
+    }
+
+    #[test]
+    fn test_replace_scalar_keeps_comments() {
+        let source = "# header\nname: Test # trailing\nparts:\n  a:\n    # comment\n    cases:\n      x:\n        expected:\n          stdout: old\n";
+
+        let result = set_scalar(
+            source,
+            &["parts", "a", "cases", "x", "expected", "stdout"],
+            "new",
+        )
+        .unwrap();
+
+        assert_eq!(result, source.replace("stdout: old", "stdout: new"));
+    }
+
+    #[test]
+    fn test_replace_keeps_trailing_comment() {
+        for (line, replaced) in [
+            ("stdout: old # note", "stdout: new # note"),
+            ("stdout: 'a # b' # note", "stdout: new # note"),
+            ("stdout: 'a # b'", "stdout: new"),
+            ("stdout: # note", "stdout: new # note"),
+        ] {
+            let result = set_scalar(&format!("x:\n  {line}\n"), &["x", "stdout"], "new").unwrap();
+
+            assert_eq!(result, format!("x:\n  {replaced}\n"));
+        }
+        let result = set_scalar("x:\n  stdout: old # note\n", &["x", "stdout"], "a\nb\n").unwrap();
+        assert_eq!(result, "x:\n  stdout: | # note\n    a\n    b\n");
+    }
+
+    #[test]
+    fn test_insert_missing_mappings() {
+        let source = "cases:\n  x:\n    stdin: hi\n  y: {}\n";
+
+        let result = set_scalar(source, &["cases", "y", "expected", "stdout"], "a\nb\n").unwrap();
+
+        assert_eq!(
+            result,
+            "cases:\n  x:\n    stdin: hi\n  y:\n    expected:\n      stdout: |\n        a\n        b\n"
+        );
+    }
+
+    #[test]
+    fn test_replace_block_scalar() {
+        let source = "x:\n  stdout: |\n    a\n    b\n  other: 1\n";
+
+        let result = set_scalar(source, &["x", "stdout"], "c").unwrap();
+
+        assert_eq!(result, "x:\n  stdout: c\n  other: 1\n");
+    }
+
+    #[test]
+    fn test_rendered_values_round_trip() {
+        for value in [
+            "plain",
+            "123",
+            "a: b",
+            "a\nb",
+            "a\nb\n",
+            "a\n\nb\n\n",
+            "  indented\nx\n",
+            "\n  lead\nx\n",
+            "\n\n\tlead\n",
+            "a\r\nb\r\n",
+            "",
+        ] {
+            let result = set_scalar("x:\n  y: 0\n", &["x", "y"], value).unwrap();
+
+            assert_eq!(parse(&result)["x"]["y"].as_str(), Some(value), "{result}");
+        }
+    }
+
+    #[test]
+    fn test_locate() {
+        let source = "name: x\nparts:\n  # comment\n  a:\n    cases:\n      \"q\": {}\n";
+
+        assert_eq!(locate(source, &["parts", "a", "cases"]), Some((4, 4, 5)));
+        assert_eq!(
+            locate(source, &["parts", "a", "cases", "q"]),
+            Some((5, 6, 3))
+        );
+        assert_eq!(locate(source, &["parts", "b", "cases"]), Some((1, 0, 5)));
+        assert_eq!(locate(source, &["other"]), None);
+    }
+
+    #[test]
+    fn test_set_collections() {
+        let source = "parts:\n  a:\n    cases: {}\n";
+        let mut case = serde_yaml::Mapping::new();
+        case.insert("stdin".into(), "".into());
+        case.insert("arguments".into(), vec!["-v"].into());
+
+        let result = set_value(
+            source,
+            &["parts", "b", "cases"],
+            &Value::Mapping(Default::default()),
+        )
+        .unwrap();
+        let result = set_value(
+            &result,
+            &["parts", "a", "cases", "x"],
+            &Value::Mapping(case),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result,
+            "parts:\n  a:\n    cases:\n      x:\n        stdin: ''\n        arguments:\n        - -v\n  b:\n    cases: {}\n"
+        );
+    }
+
+    #[test]
+    fn test_flow_style_is_rejected() {
+        let res = set_scalar("x: {y: 1}\n", &["x", "y"], "2");
+
+        assert!(matches!(res, Err(YamlEditError::FlowStyle(_))));
+    }
+}
@@ -1,12 +1,26 @@
+use std::fs::File;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use challenge_script::{
     challenge::{
-        ChallengeCaseError, ChallengeExecutionError, CommandParseError, StringReferenceError,
+        CaseOutcome, ChallengeCaseError, ChallengeConfig, ChallengeExecutionError,
+        ChallengeParseError, CommandConfig, CommandParseError, ExitCodeExpectation, MatchOptions,
+        Matcher, MatcherError, MatcherRegistry, OutputStream, StderrSink, StringReferenceError,
     },
-    run_challenge, ChallengeFileError, ProgramError,
+    dry_run_challenge, dry_run_challenges, find_nearest_challenge, run_challenge,
+    run_challenge_with, run_challenges_with, ChallengeFileError, ChallengeTarget, ProgramError,
+    RunOptions, EXIT_CONFIG_ERROR, EXIT_FILE_ERROR, EXIT_TEST_FAILURE,
 };
 
 mod utils;
 
+/// Run a single case, turning a failed outcome into an error.
+fn run_case(path: &str, cases: Vec<String>) -> Result<CaseOutcome, ProgramError> {
+    Ok(run_challenge(path, cases)?.into_result()?)
+}
+
 #[test]
 fn test_success_1() {
     test_challenge!("./tests/data/test1.yml", "test");
@@ -47,6 +61,28 @@ fn test_success_command_templating() {
     test_challenge!("./tests/data/template_test.yml", "group1", "test");
     test_challenge!("./tests/data/template_test.yml", "group2", "test");
     test_challenge!("./tests/data/template_test.yml", "group3", "test");
+    test_challenge!("./tests/data/template_test.yml", "group4", "stdin");
+}
+
+#[test]
+fn test_success_vars() {
+    test_challenge!("./tests/data/vars_test.yml", "scoped", "inherited");
+    test_challenge!("./tests/data/vars_test.yml", "scoped", "override");
+    test_challenge!("./tests/data/vars_test.yml", "builtins", "names");
+    test_challenge!("./tests/data/vars_test.yml", "files", "input_file");
+    test_challenge!("./tests/data/vars_test.yml", "files", "challenge_dir");
+    test_challenge!("./tests/data/vars_test.yml", "files", "stdin");
+}
+
+#[test]
+fn test_error_builtin_variable() {
+    let res = run_case("./tests/data/bad_vars.yml", vec!["sample".to_owned()]);
+
+    if let Err(ProgramError::ParseError(ChallengeParseError::BuiltinVariable(name))) = res {
+        assert_eq!(name, "case");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
 }
 
 #[test]
@@ -55,20 +91,376 @@ fn test_success_arguments() {
     test_challenge!("./tests/data/test2.yml", "args", "test4");
 }
 
+#[test]
+fn test_success_timeout() {
+    test_challenge!("./tests/data/timeout_test.yml", "quick", "test");
+}
+
+#[test]
+fn test_error_timeout_inherited() {
+    let res = run_case(
+        "./tests/data/timeout_test.yml",
+        vec!["slow".to_owned(), "inherited".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::TimedOut { elapsed, limit })) =
+        res
+    {
+        assert_eq!(limit, Duration::from_millis(200));
+        assert!(elapsed >= limit);
+        assert!(elapsed < Duration::from_secs(5));
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_timeout_override() {
+    let res = run_case(
+        "./tests/data/timeout_test.yml",
+        vec!["slow".to_owned(), "override".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::TimedOut { limit, .. })) = res
+    {
+        assert_eq!(limit, Duration::from_millis(300));
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_timeout_kills_process_group() {
+    let started = Instant::now();
+    let res = run_case(
+        "./tests/data/timeout_test.yml",
+        vec!["orphan".to_owned(), "test".to_owned()],
+    );
+
+    let Err(ProgramError::ExecutionError(ChallengeExecutionError::TimedOut { .. })) = res else {
+        panic!("Unexpected Error: {res:?}");
+    };
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_success_exit_code() {
+    test_challenge!("./tests/data/exit_code_test.yml", "crash", "exact");
+    test_challenge!("./tests/data/exit_code_test.yml", "crash", "list");
+    test_challenge!("./tests/data/exit_code_test.yml", "crash", "any");
+    test_challenge!("./tests/data/exit_code_test.yml", "signal", "name");
+    test_challenge!("./tests/data/exit_code_test.yml", "signal", "number");
+}
+
+#[test]
+fn test_error_exit_code_after_correct_output() {
+    let res = run_case(
+        "./tests/data/exit_code_test.yml",
+        vec!["crash".to_owned(), "default".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedExitStatus {
+        expected,
+        status,
+        ..
+    })) = res
+    {
+        assert_eq!(expected, ExitCodeExpectation::Codes(vec![0]));
+        assert_eq!(status.code(), Some(3));
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_exit_code_without_expectation() {
+    let res = run_case(
+        "./tests/data/exit_code_test.yml",
+        vec!["silent".to_owned(), "test".to_owned()],
+    );
+
+    let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedExitStatus { .. })) =
+        res
+    else {
+        panic!("Unexpected Error: {res:?}");
+    };
+}
+
+#[test]
+fn test_error_exit_code_signal() {
+    let res = run_case(
+        "./tests/data/exit_code_test.yml",
+        vec!["signal".to_owned(), "default".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedExitStatus {
+        status,
+        ..
+    })) = res
+    {
+        assert_eq!(status.code(), None);
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_success_stderr() {
+    test_challenge!("./tests/data/stderr_test.yml", "diagnostics", "live");
+    test_challenge!("./tests/data/stderr_test.yml", "diagnostics", "capture");
+}
+
+/// Collects what's written to it, so tests can read it back.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_live_stderr_sink() {
+    let buffer = SharedBuffer::default();
+    let options = RunOptions {
+        live_stderr: Some(StderrSink::new(buffer.clone())),
+        ..RunOptions::default()
+    };
+    for case in ["live", "capture"] {
+        let cases = vec!["diagnostics".to_owned(), case.to_owned()];
+        let outcome = run_challenge_with("./tests/data/stderr_test.yml", cases, &options).unwrap();
+        assert!(outcome.is_passed(), "{outcome:?}");
+        assert_eq!(outcome.stderr, "warn\n");
+    }
+    assert_eq!(buffer.0.lock().unwrap().as_slice(), b"warn\n");
+}
+
+#[test]
+fn test_captured_stderr_shown_on_exit_status() {
+    let run = |cases: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_challenge_script"))
+            .arg("./tests/data/stderr_test.yml")
+            .args(cases)
+            .output()
+            .unwrap()
+    };
+
+    let passed = run(&["diagnostics", "capture"]);
+    assert!(passed.status.success(), "{passed:?}");
+    assert!(!String::from_utf8_lossy(&passed.stderr).contains("warn"));
+
+    let crashed = run(&["crash", "test"]);
+    assert_eq!(crashed.status.code(), Some(EXIT_TEST_FAILURE.into()));
+    assert!(
+        String::from_utf8_lossy(&crashed.stderr).ends_with("\nstderr:\nboom\n"),
+        "{crashed:?}"
+    );
+}
+
+#[test]
+fn test_error_unexpected_stderr() {
+    let res = run_case(
+        "./tests/data/stderr_test.yml",
+        vec!["diagnostics".to_owned(), "mismatch".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedOutput {
+        stream,
+        expected,
+        actual,
+        ..
+    })) = res
+    {
+        assert_eq!(stream, OutputStream::Stderr);
+        assert_eq!(expected, "error");
+        assert_eq!(actual, "warn");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_exit_code_captures_stderr() {
+    let res = run_case(
+        "./tests/data/stderr_test.yml",
+        vec!["crash".to_owned(), "test".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedExitStatus {
+        stderr,
+        ..
+    })) = res
+    {
+        assert_eq!(stderr, "boom\n");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_exit_code_shows_stderr() {
+    let res = run_case(
+        "./tests/data/stderr_test.yml",
+        vec!["crash".to_owned(), "test".to_owned()],
+    );
+
+    if let Err(err) = res {
+        assert!(err.to_string().ends_with("\nstderr:\nboom"), "{err}");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_success_matchers() {
+    test_challenge!("./tests/data/matcher_test.yml", "matchers", "exact");
+    test_challenge!("./tests/data/matcher_test.yml", "matchers", "regex");
+    test_challenge!("./tests/data/matcher_test.yml", "matchers", "contains");
+    test_challenge!("./tests/data/matcher_test.yml", "matchers", "float");
+    test_challenge!(
+        "./tests/data/matcher_test.yml",
+        "matchers",
+        "float_relative"
+    );
+    test_challenge!("./tests/data/matcher_test.yml", "unordered", "test");
+}
+
+#[test]
+fn test_success_custom_matcher() {
+    struct CaseInsensitive;
+    impl Matcher for CaseInsensitive {
+        fn matches(
+            &self,
+            expected: &str,
+            actual: &str,
+            _: &MatchOptions,
+        ) -> Result<bool, MatcherError> {
+            Ok(expected.trim().eq_ignore_ascii_case(actual.trim()))
+        }
+    }
+    let mut matchers = MatcherRegistry::default();
+    matchers.register("case-insensitive", CaseInsensitive);
+
+    let file = File::open("./tests/data/matcher_test.yml").unwrap();
+    let config = ChallengeConfig::parse_file(file).unwrap();
+    let (command, case) = config
+        .resolve_case(
+            vec!["matchers".to_owned(), "custom".to_owned()].into_iter(),
+            CommandConfig::default(),
+        )
+        .unwrap();
+
+    case.execute_with("./tests/data", &command, &matchers)
+        .into_result()
+        .unwrap();
+
+    let options = RunOptions {
+        matchers,
+        ..RunOptions::default()
+    };
+    let cases = vec!["matchers".to_owned(), "custom".to_owned()];
+    let report =
+        run_challenges_with("./tests/data/matcher_test.yml", cases, &options, |_| {}).unwrap();
+    assert!(report.is_success(), "{report:#?}");
+}
+
+#[test]
+fn test_error_matcher_mismatch() {
+    let res = run_case(
+        "./tests/data/matcher_test.yml",
+        vec!["matchers".to_owned(), "float_mismatch".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedOutput {
+        expected,
+        actual,
+        ..
+    })) = res
+    {
+        assert_eq!(expected, "3.14");
+        assert_eq!(actual, "3.15");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_unknown_matcher() {
+    let res = run_case(
+        "./tests/data/matcher_test.yml",
+        vec!["matchers".to_owned(), "unknown".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnknownMatcher(name))) = res {
+        assert_eq!(name, "nonsense");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_error_matcher_without_value() {
+    let res = run_case("./tests/data/bad_matcher.yml", vec!["test".to_owned()]);
+
+    if let Err(ProgramError::ParseError(ChallengeParseError::InvalidMatchValue(case))) = res {
+        assert_eq!(case, "test");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
+#[test]
+fn test_success_checker() {
+    test_challenge!("./tests/data/checker_test.yml", "ordering", "sorted");
+    test_challenge!("./tests/data/checker_test.yml", "echo", "immediate");
+    test_challenge!("./tests/data/checker_test.yml", "echo", "file");
+    test_challenge!("./tests/data/checker_test.yml", "echo", "templated");
+}
+
+#[test]
+fn test_error_checker_rejected() {
+    let res = run_case(
+        "./tests/data/checker_test.yml",
+        vec!["ordering".to_owned(), "rejected".to_owned()],
+    );
+
+    if let Err(ProgramError::ExecutionError(ChallengeExecutionError::CheckerRejected {
+        status,
+        message,
+    })) = res
+    {
+        assert_eq!(status.code(), Some(1));
+        assert_eq!(message, "nope");
+    } else {
+        panic!("Unexpected result: {:?}", res);
+    }
+}
+
 #[test]
 fn test_error_expected() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/test3",
         vec!["group1".to_owned(), "error".to_owned()],
     );
 
     if let Err(ProgramError::ExecutionError(ChallengeExecutionError::UnexpectedOutput {
+        stream,
         expected,
         actual,
+        diff,
     })) = res
     {
+        assert_eq!(stream, OutputStream::Stdout);
         assert_eq!(expected, "test_1");
         assert_eq!(actual, "test_2");
+        assert_eq!(
+            diff.render(false),
+            "--- expected\n+++ actual\n@@ -1,1 +1,1 @@\n-test_1\n+test_2\n"
+        );
     } else {
         panic!("Unexpected result: {:?}", res);
     }
@@ -76,7 +468,7 @@ fn test_error_expected() {
 
 #[test]
 fn test_error_case_not_found_root() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/test3",
         vec!["group2".to_owned(), "error".to_owned()],
     );
@@ -84,10 +476,14 @@ fn test_error_case_not_found_root() {
     if let Err(ProgramError::InputCaseError(ChallengeCaseError::CaseNotFound {
         case,
         config_name,
+        suggestions,
+        available,
     })) = res
     {
         assert_eq!(config_name, "Test 3");
         assert_eq!(case, "group2");
+        assert_eq!(suggestions, ["group1"]);
+        assert_eq!(available, ["group1"]);
     } else {
         panic!("Unexpected result: {:?}", res);
     }
@@ -95,7 +491,7 @@ fn test_error_case_not_found_root() {
 
 #[test]
 fn test_error_case_not_found_nested() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/test3",
         vec!["group1".to_owned(), "nonexistent".to_owned()],
     );
@@ -103,18 +499,38 @@ fn test_error_case_not_found_nested() {
     if let Err(ProgramError::InputCaseError(ChallengeCaseError::CaseNotFound {
         case,
         config_name,
+        suggestions,
+        available,
     })) = res
     {
         assert_eq!(config_name, "group1");
         assert_eq!(case, "nonexistent");
+        assert!(suggestions.is_empty());
+        assert_eq!(available, ["test", "error"]);
     } else {
         panic!("Unexpected result: {:?}", res);
     }
 }
 
+#[test]
+fn test_error_case_not_found_message() {
+    let res = run_case(
+        "./tests/data/test3",
+        vec!["group1".to_owned(), "tset".to_owned()],
+    );
+
+    let Err(err) = res else {
+        panic!("Unexpected result: {res:?}");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Could not find case 'tset' in config 'group1'. Did you mean 'test'? (available: test, error)"
+    );
+}
+
 #[test]
 fn test_error_case_not_enough_cases() {
-    let res = run_challenge("./tests/data/test3", vec!["group1".to_owned()]);
+    let res = run_case("./tests/data/test3", vec!["group1".to_owned()]);
 
     if let Err(ProgramError::InputCaseError(ChallengeCaseError::TooManyCases(group))) = res {
         assert_eq!(group, "group1");
@@ -125,7 +541,7 @@ fn test_error_case_not_enough_cases() {
 
 #[test]
 fn test_error_empty_command() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/bad.yml",
         vec!["empty_command".to_owned(), "doesn't matter".to_owned()],
     );
@@ -140,7 +556,7 @@ fn test_error_empty_command() {
 
 #[test]
 fn test_error_malformed_command() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/bad.yml",
         vec!["malformed_command".to_owned(), "whatever".to_owned()],
     );
@@ -157,7 +573,7 @@ fn test_error_malformed_command() {
 
 #[test]
 fn test_error_input_file_not_found() {
-    let res = run_challenge(
+    let res = run_case(
         "./tests/data/bad.yml",
         vec!["bad_input".to_owned(), "nonexistent".to_owned()],
     );
@@ -172,18 +588,165 @@ fn test_error_input_file_not_found() {
 
 #[test]
 fn test_error_challenge_file_not_found() {
-    let res = run_challenge("./tests/data/empty/challenge.yml", vec![]);
+    let res = run_case("./tests/data/empty/challenge.yml", vec![]);
 
     let Err(ProgramError::InputFileError(ChallengeFileError::FileDoesNotExist(_))) = res else {
         panic!("Unexpected Error: {res:?}");
     };
 }
+
+#[test]
+fn test_dry_run() {
+    let dir = std::path::absolute("./tests/data").unwrap();
+
+    let planned = dry_run_challenge(
+        "./tests/data/env_test.yml",
+        vec!["group2".to_owned(), "override".to_owned()],
+    )
+    .unwrap();
+    assert_eq!(planned.parent_path, ["group2"]);
+    assert_eq!(
+        planned.shell_line,
+        format!(
+            "(cd {} && env 'TEST_GOODBYE=end' 'TEST_HELLO=world3' sh -c 'echo $TEST_HELLO-$TEST_GOODBYE' < /dev/null)",
+            dir.display()
+        )
+    );
+
+    let planned = dry_run_challenge(
+        "./tests/data/test3",
+        vec!["group1".to_owned(), "test".to_owned()],
+    )
+    .unwrap();
+    assert!(planned.shell_line.ends_with(" < input.txt)"));
+
+    let planned = dry_run_challenges("./tests/data/template_test.yml", vec![]).unwrap();
+    let lines = planned
+        .iter()
+        .map(|p| p.shell_line.as_str())
+        .collect::<Vec<_>>();
+    assert!(lines[1].ends_with("&& echo group2 and test < /dev/null)"));
+    assert!(lines[2].ends_with("&& echo '{{part}}' and '{{case}}' < /dev/null)"));
+}
+
+#[test]
+fn test_error_exit_codes() {
+    let not_found = run_case("./tests/data/empty/challenge.yml", vec![]).unwrap_err();
+    assert_eq!(not_found.exit_code(), EXIT_FILE_ERROR);
+
+    let bad_case = run_case("./tests/data/test3", vec!["group2".to_owned()]).unwrap_err();
+    assert_eq!(bad_case.exit_code(), EXIT_CONFIG_ERROR);
+
+    let wrong_output = run_case(
+        "./tests/data/test3",
+        vec!["group1".to_owned(), "error".to_owned()],
+    )
+    .unwrap_err();
+    assert_eq!(wrong_output.exit_code(), EXIT_TEST_FAILURE);
+}
+
 #[test]
 fn test_error_challenge_file_not_found_in_directory() {
-    let res = run_challenge("./tests/data/empty", vec![]);
+    let res = run_case("./tests/data/empty", vec![]);
 
     let Err(ProgramError::InputFileError(ChallengeFileError::FileNotFoundInDirectory(_))) = res
     else {
         panic!("Unexpected Error: {res:?}");
     };
 }
+
+#[test]
+fn test_find_nearest_challenge() {
+    let data = std::path::absolute("./tests/data").unwrap();
+    assert_eq!(
+        find_nearest_challenge(data.join("includes/day_template")).unwrap(),
+        ChallengeTarget::Path(data.join("includes/challenge.yml"))
+    );
+    assert_eq!(
+        find_nearest_challenge(data.join("includes/day01")).unwrap(),
+        ChallengeTarget::Path(data.join("includes/day01/challenge.yml"))
+    );
+    assert_eq!(
+        find_nearest_challenge(data.join("workspace/notes")).unwrap(),
+        ChallengeTarget::Path(data.join("workspace/challenge-workspace.yml"))
+    );
+}
+
+#[test]
+fn test_find_nearest_workspace_member() {
+    let workspace = std::path::absolute("./tests/data/workspace").unwrap();
+    let target = find_nearest_challenge(workspace.join("2023/day05")).unwrap();
+    assert_eq!(
+        target,
+        ChallengeTarget::Member {
+            workspace: workspace.clone(),
+            file: workspace.join("2023/day05/challenge.yml"),
+        }
+    );
+
+    // The member has no command of its own, so it only runs with the
+    // workspace's.
+    let outcome = run_challenge(target, vec![]).unwrap();
+    assert!(outcome.is_passed(), "{outcome:?}");
+    assert_eq!(outcome.stdout, "seeds");
+
+    // Hidden directories aren't part of the workspace.
+    assert_eq!(
+        find_nearest_challenge(workspace.join(".hidden/day99")).unwrap(),
+        ChallengeTarget::Path(workspace.join(".hidden/day99/challenge.yml"))
+    );
+}
+
+#[test]
+fn test_run_named_case_from_challenge_dir() {
+    // No path is given, so `group1` and `test` are both case selectors.
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_challenge_script"))
+        .args(["group1", "test"])
+        .current_dir("./tests/data/test3")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Passed group1/test"));
+}
+
+#[test]
+fn test_error_malformed_output_location() {
+    let res = run_case("./tests/data/malformed_output.yml", vec![]);
+
+    let Err(ProgramError::ParseError(ChallengeParseError::Invalid(diagnostic))) = res else {
+        panic!("Unexpected Error: {res:?}");
+    };
+    assert_eq!(diagnostic.path, "parts.day3.cases.sample.expected.stdout");
+    assert_eq!(diagnostic.location, Some((8, 11)));
+    assert_eq!(diagnostic.reasons.len(), 3);
+    assert!(diagnostic.reasons[0].starts_with("as text: "));
+
+    let message = diagnostic.to_string();
+    assert!(message.starts_with("./tests/data/malformed_output.yml:8:11: "));
+    assert!(message.contains("8 |           stdout: [1, 2]\n  |           ^^^^^^"));
+}
+
+#[test]
+fn test_error_malformed_timeout_location() {
+    let res = run_case("./tests/data/malformed_timeout.yml", vec![]);
+
+    let Err(err @ ProgramError::ParseError(ChallengeParseError::Invalid(_))) = res else {
+        panic!("Unexpected Error: {res:?}");
+    };
+    assert_eq!(err.exit_code(), EXIT_CONFIG_ERROR);
+    assert!(err.to_string().starts_with(
+        "./tests/data/malformed_timeout.yml:7:9: Malformed timeout 'soon' \
+         (at 'parts.day3.cases.sample.timeout')"
+    ));
+}
+
+#[test]
+fn test_error_unknown_key() {
+    let res = run_case("./tests/data/unknown_key.yml", vec!["test".to_owned()]);
+
+    let Err(ProgramError::ParseError(ChallengeParseError::Invalid(diagnostic))) = res else {
+        panic!("Unexpected Error: {res:?}");
+    };
+    assert_eq!(diagnostic.path, "cases.test.expected.exitcode");
+    assert_eq!(diagnostic.message, "Unknown key 'exitcode'");
+}
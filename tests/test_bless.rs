@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::ChallengeCaseError;
+use challenge_script::{
+    bless_challenge, bless_challenges, run_challenges, BlessOutcome, ProgramError,
+};
+
+const CHALLENGE: &str = r##"# Ported to a new language
+name: Bless Test
+parts:
+  # Comments and key order are kept
+  grid:
+    command: ["printf", "#.#\n.#.\n"]
+    cases:
+      file:
+        expected:
+          stdout:
+            file: expected.txt
+      immediate:
+        expected:
+          stdout: wrong # will be replaced
+      empty: {}
+  answer:
+    command: echo 42
+    cases:
+      exact:
+        expected:
+          stdout:
+            match: exact
+            value: "41"
+      regex:
+        expected:
+          stdout:
+            match: regex
+            value: '^\d+$'
+  crash:
+    command: ["sh", "-c", "echo partial; exit 3"]
+    cases:
+      status:
+        expected:
+          stdout: old
+"##;
+
+const BLESSED: &str = r##"# Ported to a new language
+name: Bless Test
+parts:
+  # Comments and key order are kept
+  grid:
+    command: ["printf", "#.#\n.#.\n"]
+    cases:
+      file:
+        expected:
+          stdout:
+            file: expected.txt
+      immediate:
+        expected:
+          stdout: | # will be replaced
+            #.#
+            .#.
+      empty:
+        expected:
+          stdout: |
+            #.#
+            .#.
+  answer:
+    command: echo 42
+    cases:
+      exact:
+        expected:
+          stdout:
+            match: exact
+            value: |
+              42
+      regex:
+        expected:
+          stdout:
+            match: regex
+            value: '^\d+$'
+  crash:
+    command: ["sh", "-c", "echo partial; exit 3"]
+    cases:
+      status:
+        expected:
+          stdout: old
+"##;
+
+#[test]
+fn test_bless() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bless");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("challenge.yml"), CHALLENGE).unwrap();
+    fs::write(dir.join("expected.txt"), "old").unwrap();
+
+    let blessed = bless_challenges(&dir, vec![]).unwrap();
+
+    let outcome = |name: &str| {
+        blessed
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| c.outcome.clone())
+            .unwrap()
+    };
+    assert_eq!(
+        outcome("file"),
+        BlessOutcome::WroteFile(dir.join("expected.txt"))
+    );
+    assert_eq!(outcome("immediate"), BlessOutcome::UpdatedConfig);
+    assert_eq!(outcome("empty"), BlessOutcome::UpdatedConfig);
+    assert_eq!(outcome("exact"), BlessOutcome::UpdatedConfig);
+    assert!(matches!(outcome("regex"), BlessOutcome::Skipped(_)));
+    assert!(matches!(outcome("status"), BlessOutcome::Skipped(_)));
+
+    assert_eq!(
+        fs::read_to_string(dir.join("expected.txt")).unwrap(),
+        "#.#\n.#.\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("challenge.yml")).unwrap(),
+        BLESSED
+    );
+    run_challenges(&dir, vec![]).unwrap();
+
+    fs::write(dir.join("expected.txt"), "old").unwrap();
+    let err = bless_challenge(&dir, vec!["grid".to_owned()]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::InputCaseError(ChallengeCaseError::TooManyCases(_))
+        ),
+        "{err:?}"
+    );
+    let case = bless_challenge(&dir, vec!["grid".to_owned(), "file".to_owned()]).unwrap();
+    assert_eq!(case.name, "file");
+    assert_eq!(
+        fs::read_to_string(dir.join("expected.txt")).unwrap(),
+        "#.#\n.#.\n"
+    );
+}
+
+#[test]
+fn test_bless_round_trip() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bless_round_trip");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("challenge.yml"),
+        r#"name: Round Trip
+parts:
+  lead:
+    command: ["printf", "\n  lead\nx\n"]
+    cases:
+      exact:
+        expected:
+          stdout:
+            match: exact
+            value: old
+  crlf:
+    command: ["printf", "a\r\nb\r\n"]
+    cases:
+      exact:
+        expected:
+          stdout:
+            match: exact
+            value: old
+"#,
+    )
+    .unwrap();
+
+    bless_challenges(&dir, vec![]).unwrap();
+
+    let report = run_challenges(&dir, vec![]).unwrap();
+    assert!(
+        report.cases.iter().all(|case| case.is_passed()),
+        "{}",
+        fs::read_to_string(dir.join("challenge.yml")).unwrap()
+    );
+}
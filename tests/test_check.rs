@@ -0,0 +1,115 @@
+use challenge_script::check_challenge;
+
+#[test]
+fn test_check_valid_file() {
+    let diagnostics = check_challenge("./tests/data/test3").unwrap();
+
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+}
+
+#[test]
+fn test_check_reports_every_problem() {
+    let diagnostics = check_challenge("./tests/data/check_test.yml").unwrap();
+
+    let found = diagnostics
+        .iter()
+        .map(|d| (d.path.as_str(), d.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            ("parts.templates.command", "Unknown placeholder '{{input}}'"),
+            ("parts.templates.arguments", "Unknown placeholder '{{day}}'"),
+            (
+                "parts.templates.cases.sample.stdin.file",
+                "File 'missing-input.txt' does not exist"
+            ),
+            (
+                "parts.templates.cases.sample.expected.exitcode",
+                "Unknown key 'exitcode'"
+            ),
+            ("parts.no_command", "Part 'no_command' has no command"),
+            ("parts.malformed.command", "Malformed command string"),
+        ]
+    );
+    assert_eq!(diagnostics[3].reasons[0], "did you mean 'exit_code'?");
+    assert_eq!(diagnostics[4].location, Some((13, 3)));
+}
+
+#[test]
+fn test_check_vars() {
+    let diagnostics = check_challenge("./tests/data/vars_test.yml").unwrap();
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    let diagnostics = check_challenge("./tests/data/bad_vars.yml").unwrap();
+    let found = diagnostics
+        .iter()
+        .map(|d| (d.path.as_str(), d.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [
+            ("command", "Unknown placeholder '{{input_file}}'"),
+            (
+                "vars.case",
+                "Variable 'case' is built in, so it can't be set in `vars`"
+            ),
+            ("cases.sample.stdin", "Unknown placeholder '{{missing}}'"),
+        ]
+    );
+}
+
+#[test]
+fn test_check_stdin_placeholders_only_when_templated() {
+    let diagnostics = check_challenge("./tests/data/template_test.yml").unwrap();
+    assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+    let dir = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("check_stdin");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::create_dir_all(dir.join("included")).unwrap();
+    std::fs::write(
+        dir.join("included/challenge.yml"),
+        "name: Included\ncases:\n  braces:\n    stdin: \"{{not_a_var}}\"\n",
+    )
+    .unwrap();
+    let file = dir.join("challenge.yml");
+    std::fs::write(
+        &file,
+        r#"name: Stdin placeholders
+parts:
+  untemplated:
+    command:
+      script: cat
+      template: false
+    parts:
+      included:
+        include: included
+  data:
+    command:
+      script: cat
+      template: false
+    cases:
+      braces:
+        stdin: "{{not_a_var}}"
+  templated:
+    command: cat
+    cases:
+      braces:
+        stdin: "{{not_a_var}}"
+"#,
+    )
+    .unwrap();
+
+    let diagnostics = check_challenge(&file).unwrap();
+    let found = diagnostics
+        .iter()
+        .map(|d| (d.path.as_str(), d.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        [(
+            "parts.templated.cases.braces.stdin",
+            "Unknown placeholder '{{not_a_var}}'"
+        )]
+    );
+}
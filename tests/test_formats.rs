@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::RunReport;
+use challenge_script::{
+    bless_challenges, check_challenge, convert_challenge, new_case, run_challenges, ConvertError,
+    NewCaseOptions, ProgramError,
+};
+
+mod utils;
+
+fn convert_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_success_toml() {
+    test_all_challenges!("./tests/data/formats/toml");
+}
+
+#[test]
+fn test_success_json() {
+    test_all_challenges!("./tests/data/formats/json/challenge.json");
+}
+
+#[test]
+fn test_check_locations() {
+    for (file, stdin, exitcode) in [
+        ("check_test.toml", (5, 11), (6, 27)),
+        ("check_test.json", (6, 18), (7, 35)),
+    ] {
+        let diagnostics = check_challenge(format!("./tests/data/formats/{file}")).unwrap();
+        let found = diagnostics
+            .iter()
+            .map(|d| (d.path.as_str(), d.location))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            [
+                ("cases.sample.stdin.file", Some(stdin)),
+                ("cases.sample.expected.exitcode", Some(exitcode)),
+            ],
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn test_check_syntax_error() {
+    let dir = convert_dir("format_syntax_error");
+    let file = dir.join("challenge.toml");
+    fs::write(&file, "name = \"Broken\n").unwrap();
+
+    let diagnostics = check_challenge(&file).unwrap();
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0].location, Some((1, 15)));
+}
+
+#[test]
+fn test_convert() {
+    let dir = convert_dir("format_convert");
+    fs::copy("./tests/data/test3/input.txt", dir.join("input.txt")).unwrap();
+
+    let toml = dir.join("challenge.toml");
+    convert_challenge("./tests/data/test3", &toml).unwrap();
+    let json = dir.join("challenge.json");
+    convert_challenge(&toml, &json).unwrap();
+    let yaml = dir.join("challenge.yml");
+    convert_challenge(&json, &yaml).unwrap();
+
+    let outcomes = |report: RunReport| {
+        report
+            .cases
+            .into_iter()
+            .map(|case| (case.is_passed(), case.name))
+            .collect::<Vec<_>>()
+    };
+    let original = outcomes(run_challenges("./tests/data/test3", vec![]).unwrap());
+    for file in [&toml, &json, &yaml] {
+        let converted = outcomes(run_challenges(file, vec![]).unwrap());
+        assert_eq!(converted, original, "{}", file.display());
+    }
+
+    let err = convert_challenge(&toml, &json).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::ConvertError(ConvertError::AlreadyExists(_))
+        ),
+        "{err:?}"
+    );
+    let err = convert_challenge(&toml, dir.join("challenge.txt")).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::ConvertError(ConvertError::UnknownFormat(_))
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_convert_null_to_toml() {
+    let dir = convert_dir("format_convert_null");
+    let yaml = dir.join("challenge.yml");
+    fs::write(
+        &yaml,
+        "name: Nulls\ntimeout: null\ncommand: cat\ncases:\n  sample:\n    stdin: ~\n    expected:\n      stdout: \"\"\n",
+    )
+    .unwrap();
+
+    let toml = dir.join("challenge.toml");
+    convert_challenge(&yaml, &toml).unwrap();
+    let converted = fs::read_to_string(&toml).unwrap();
+    assert!(!converted.contains("timeout"), "{converted}");
+    assert!(!converted.contains("stdin"), "{converted}");
+    let report = run_challenges(&toml, vec![]).unwrap();
+    assert!(
+        report.cases.iter().all(|case| case.is_passed()),
+        "{report:?}"
+    );
+}
+
+#[test]
+fn test_edit_toml() {
+    let dir = convert_dir("format_edit");
+    let file = dir.join("challenge.toml");
+    fs::write(
+        &file,
+        "# Kept\nname = \"Edit\"\ncommand = \"echo 42\"\n\n[cases.sample]\nexpected = { stdout = \"41\" }\n",
+    )
+    .unwrap();
+
+    bless_challenges(&file, vec![]).unwrap();
+    new_case(&file, "extra", NewCaseOptions::default()).unwrap();
+    assert_eq!(
+        fs::read_to_string(&file).unwrap(),
+        "# Kept\nname = \"Edit\"\ncommand = \"echo 42\"\n\n[cases.sample]\nexpected = { stdout = \"42\" }\n\n[cases.extra]\nstdin = \"\"\nexpected = { stdout = \"\" }\n"
+    );
+}
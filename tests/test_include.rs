@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::ChallengeParseError;
+use challenge_script::{
+    bless_challenges, check_challenge, dry_run_challenge, list_challenge, new_case, run_challenges,
+    BlessOutcome, ListedNode, NewCaseOptions, ProgramError, ScaffoldError,
+};
+
+#[test]
+fn test_includes() {
+    let report = run_challenges("./tests/data/includes", vec![]).unwrap();
+    assert!(report.is_success(), "{report:#?}");
+    let names = report
+        .cases
+        .iter()
+        .map(|case| {
+            [case.parent_path.clone(), vec![case.name.clone()]]
+                .concat()
+                .join("/")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "local/root",
+            "days/day01/sample",
+            "days/day02/part1/greeting",
+            "extra/echo"
+        ]
+    );
+}
+
+#[test]
+fn test_include_pattern_is_a_group() {
+    let ListedNode::Group { parts, .. } = list_challenge("./tests/data/includes").unwrap() else {
+        panic!("Expected the challenge to be a group");
+    };
+    let days = parts.iter().find(|part| part.name() == "days").unwrap();
+    let ListedNode::Group { parts: days, .. } = days else {
+        panic!("Expected 'days' to be a group");
+    };
+    let names = days.iter().map(ListedNode::name).collect::<Vec<_>>();
+    assert_eq!(names, ["day01", "day02"]);
+}
+
+#[test]
+fn test_included_cases_run_in_the_challenge_directory() {
+    let planned = dry_run_challenge(
+        "./tests/data/includes",
+        vec!["days/day01/sample".to_owned()],
+    )
+    .unwrap();
+    // Only the files it refers to are found from the included file.
+    let dir = std::path::absolute("./tests/data/includes").unwrap();
+    assert_eq!(
+        planned.shell_line,
+        format!(
+            "(cd {} && env 'GREETING=hello' cat < day01/input.txt)",
+            dir.display()
+        )
+    );
+}
+
+#[test]
+fn test_check_includes() {
+    assert!(check_challenge("./tests/data/includes").unwrap().is_empty());
+}
+
+#[test]
+fn test_check_included_builtin_vars() {
+    // `{{path}}` in an included file starts from the including challenge.
+    assert!(check_challenge("./tests/data/include_vars")
+        .unwrap()
+        .is_empty());
+    let report = run_challenges("./tests/data/include_vars", vec![]).unwrap();
+    assert!(report.is_success(), "{report:#?}");
+}
+
+#[test]
+fn test_include_cycle() {
+    let err = run_challenges("./tests/data/include_cycle", vec![]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::ParseError(ChallengeParseError::IncludeCycle(_))
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn test_bless_included_file() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bless_include");
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(dir.join("day01")).unwrap();
+    let root = "name: Bless Include\ncommand: echo 42\nparts:\n  day01:\n    include: day01\n";
+    fs::write(dir.join("challenge.yml"), root).unwrap();
+    fs::write(
+        dir.join("day01/challenge.yml"),
+        "name: Day 1\ncases:\n  sample:\n    expected:\n      stdout: wrong\n",
+    )
+    .unwrap();
+
+    let blessed = bless_challenges(&dir, vec![]).unwrap();
+    assert_eq!(blessed[0].outcome, BlessOutcome::UpdatedConfig);
+    assert_eq!(fs::read_to_string(dir.join("challenge.yml")).unwrap(), root);
+    assert_eq!(
+        fs::read_to_string(dir.join("day01/challenge.yml")).unwrap(),
+        "name: Day 1\ncases:\n  sample:\n    expected:\n      stdout: '42'\n"
+    );
+    assert!(run_challenges(&dir, vec![]).unwrap().is_success());
+
+    let err = new_case(&dir, "day01/other", NewCaseOptions::default()).unwrap_err();
+    assert!(
+        matches!(err, ProgramError::ScaffoldError(ScaffoldError::Included(_))),
+        "{err:?}"
+    );
+}
@@ -0,0 +1,65 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::{ChallengeConfig, CommandConfig};
+
+/// Several times larger than any OS pipe buffer.
+const LINE_COUNT: usize = 300_000;
+
+fn large_input() -> String {
+    (0..LINE_COUNT).fold(String::new(), |mut acc, i| {
+        writeln!(acc, "line {i:08}").unwrap();
+        acc
+    })
+}
+
+fn run_large_case(stdin_yaml: &str, challenge_dir: PathBuf) {
+    let config_yaml = format!(
+        "name: Large IO\n\
+         command: cat\n\
+         timeout: 30s\n\
+         cases:\n  \
+           large:\n    \
+             stdin: {stdin_yaml}\n    \
+             expected:\n      \
+               stdout:\n        \
+                 file: large_input.txt\n"
+    );
+    let config = ChallengeConfig::parse_file(config_yaml.as_bytes()).unwrap();
+    let (command, case) = config
+        .resolve_case(
+            vec!["large".to_owned()].into_iter(),
+            CommandConfig::default(),
+        )
+        .unwrap();
+
+    case.execute(challenge_dir, &command).into_result().unwrap();
+}
+
+fn write_large_input(test_name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(test_name);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("large_input.txt"), large_input()).unwrap();
+    dir
+}
+
+#[test]
+fn test_large_immediate_stdin() {
+    let dir = write_large_input("large_immediate_stdin");
+    let block = large_input()
+        .lines()
+        .fold(String::from("|"), |mut acc, line| {
+            write!(acc, "\n      {line}").unwrap();
+            acc
+        });
+
+    run_large_case(&block, dir);
+}
+
+#[test]
+fn test_large_file_stdin() {
+    let dir = write_large_input("large_file_stdin");
+
+    run_large_case("\n      file: large_input.txt", dir);
+}
@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+
+use challenge_script::{list_challenge, ListedCase, ListedNode};
+
+fn find_case<'a>(node: &'a ListedNode, part: &str, case: &str) -> &'a ListedCase {
+    let ListedNode::Group { parts, .. } = node else {
+        panic!("Expected a group: {node:?}");
+    };
+    let Some(ListedNode::Part { cases, .. }) = parts.iter().find(|p| p.name() == part) else {
+        panic!("Part '{part}' not found: {node:?}");
+    };
+    cases.iter().find(|c| c.name == case).unwrap()
+}
+
+#[test]
+fn test_list_tree() {
+    let tree = list_challenge("./tests/data/order_test.yml").unwrap();
+
+    assert_eq!(tree.name(), "Order Test");
+    let ListedNode::Group { parts, .. } = &tree else {
+        panic!("Expected a group: {tree:?}");
+    };
+    let names = parts.iter().map(ListedNode::name).collect::<Vec<_>>();
+    assert_eq!(names, ["zeta", "alpha", "days"]);
+}
+
+#[test]
+fn test_list_resolves_commands() {
+    let tree = list_challenge("./tests/data/template_test.yml").unwrap();
+
+    let templated = find_case(&tree, "group2", "test");
+    assert_eq!(templated.command, ["echo", "group2", "and", "test"]);
+    let untemplated = find_case(&tree, "group3", "test");
+    assert_eq!(untemplated.command, ["echo", "{{part}}", "and", "{{case}}"]);
+
+    let args = find_case(
+        &list_challenge("./tests/data/test2.yml").unwrap(),
+        "args",
+        "test2",
+    )
+    .clone();
+    assert_eq!(args.command, ["grep", "2"]);
+    assert_eq!(
+        args.expected.stdout.and_then(|s| s.value).as_deref(),
+        Some("test_2_token")
+    );
+}
+
+#[test]
+fn test_list_merges_environment() {
+    let tree = list_challenge("./tests/data/env_test.yml").unwrap();
+
+    let case = find_case(&tree, "group2", "override");
+    assert_eq!(
+        case.environment,
+        BTreeMap::from([
+            ("TEST_HELLO".to_owned(), "world3".to_owned()),
+            ("TEST_GOODBYE".to_owned(), "end".to_owned()),
+        ])
+    );
+}
+
+#[test]
+fn test_list_json() {
+    let tree = list_challenge("./tests/data/test2.yml").unwrap();
+
+    let json = serde_json::to_value(&tree).unwrap();
+    assert_eq!(json["kind"], "group");
+    assert_eq!(json["parts"][1]["kind"], "part");
+    assert_eq!(
+        json["parts"][1]["cases"][0]["expected"]["stdout"]["file"],
+        "test2-output.txt"
+    );
+}
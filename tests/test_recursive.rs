@@ -1,4 +1,10 @@
-use challenge_script::run_challenges;
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::{CaseStatus, ChallengeCaseError, ChallengeExecutionError};
+use challenge_script::{
+    dry_run_challenges, run_challenges, run_challenges_with, ProgramError, RunOptions,
+};
 
 mod utils;
 
@@ -11,3 +17,152 @@ fn test_recursive_all() {
 fn test_recursive() {
     test_challenges!("./tests/data/test2.yml", "args");
 }
+
+#[test]
+fn test_recursive_reports_every_case() {
+    let report = run_challenges("./tests/data/stderr_test.yml", vec![]).unwrap();
+
+    assert_eq!(report.cases.len(), 4);
+    assert_eq!(report.passed(), 2);
+    assert_eq!(report.failed(), 2);
+    assert_eq!(report.errored(), 0);
+
+    let mismatch = report.cases.iter().find(|c| c.name == "mismatch").unwrap();
+    assert_eq!(mismatch.parent_path, vec!["diagnostics".to_owned()]);
+    assert_eq!(mismatch.stdout, "out\n");
+    assert_eq!(mismatch.stderr, "warn\n");
+    assert!(mismatch.exit_status.is_some_and(|s| s.success()));
+    assert!(matches!(
+        mismatch.status,
+        CaseStatus::Failed(ChallengeExecutionError::UnexpectedOutput { .. })
+    ));
+}
+
+#[test]
+fn test_recursive_fail_fast() {
+    let options = RunOptions {
+        fail_fast: true,
+        jobs: 1,
+        ..RunOptions::default()
+    };
+    let report =
+        run_challenges_with("./tests/data/stderr_test.yml", vec![], &options, |_| {}).unwrap();
+
+    assert_eq!(report.cases.len(), 4);
+    assert_eq!(report.failed(), 1);
+    let first_failure = report.cases.iter().position(|c| c.is_failed()).unwrap();
+    assert!(report.cases[first_failure + 1..]
+        .iter()
+        .all(|c| matches!(c.status, CaseStatus::Skipped(_))));
+}
+
+#[test]
+fn test_recursive_parallel() {
+    // The shared part writes to its working directory, so run a copy of the
+    // challenge from a scratch directory.
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("recursive_parallel");
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(&dir).unwrap();
+    let file = dir.join("parallel_test.yml");
+    fs::copy("./tests/data/parallel_test.yml", &file).unwrap();
+
+    let options = RunOptions {
+        fail_fast: false,
+        jobs: 8,
+        ..RunOptions::default()
+    };
+    let mut streamed = Vec::new();
+    let report = run_challenges_with(&file, vec![], &options, |c| {
+        streamed.push(format!("{}/{}", c.parent_path.join("/"), c.name))
+    })
+    .unwrap();
+
+    // Each shared case reads back what it wrote, so it only passes if no
+    // other shared case ran in the meantime.
+    assert!(report.is_success(), "{report:#?}");
+    let reported = report
+        .cases
+        .iter()
+        .map(|c| format!("{}/{}", c.parent_path.join("/"), c.name))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reported,
+        ["shared/a", "shared/b", "shared/c", "slow/a", "slow/b", "slow/c", "slow/d"]
+    );
+    assert_eq!(streamed, reported);
+    assert!(!dir.join("parallel_shared.tmp").exists());
+}
+
+#[test]
+fn test_recursive_declaration_order() {
+    let report = run_challenges("./tests/data/order_test.yml", vec![]).unwrap();
+
+    let names = report
+        .cases
+        .iter()
+        .map(|c| format!("{}/{}", c.parent_path.join("/"), c.name))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "zeta/second",
+            "zeta/first",
+            "alpha/only",
+            "days/day1",
+            "days/day2",
+            "days/day10"
+        ]
+    );
+    assert_eq!(report.cases[0].stdout, "zeta/second\n");
+}
+
+fn selected(selectors: &[&str]) -> Result<Vec<String>, ProgramError> {
+    let selectors = selectors.iter().map(|s| s.to_string()).collect();
+    Ok(
+        dry_run_challenges("./tests/data/selector_test.yml", selectors)?
+            .into_iter()
+            .map(|c| format!("{}/{}", c.parent_path.join("/"), c.name))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_recursive_selectors() {
+    assert_eq!(
+        selected(&["day03/part2/*"]).unwrap(),
+        ["day03/part2/sample", "day03/part2/real"]
+    );
+    assert_eq!(
+        selected(&["**/sample*"]).unwrap(),
+        ["day03/part1/sample", "day03/part2/sample", "day04/sample2"]
+    );
+    assert_eq!(
+        selected(&["day03/{part1,part2}/real"]).unwrap(),
+        ["day03/part1/real", "day03/part2/real"]
+    );
+    assert_eq!(
+        selected(&["day04/*", "day03/part1"]).unwrap(),
+        ["day03/part1/sample", "day03/part1/real", "day04/sample2"]
+    );
+}
+
+#[test]
+fn test_recursive_selector_matches_nothing() {
+    let res = selected(&["day03/part2/*", "day03/part3/*"]);
+
+    let Err(ProgramError::InputCaseError(ChallengeCaseError::SelectorMatchedNothing {
+        selector,
+        searched,
+        suggestions,
+        available,
+    })) = res
+    else {
+        panic!("Unexpected result: {res:?}");
+    };
+    assert_eq!(selector, "day03/part3/*");
+    assert_eq!(searched, "day03");
+    assert_eq!(suggestions, ["part1", "part2"]);
+    assert_eq!(available, ["part1", "part2"]);
+}
@@ -0,0 +1,111 @@
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::{
+    check_challenge, init_challenge, list_challenge, new_case, new_part, NewCaseOptions,
+    ProgramError, ScaffoldError,
+};
+
+const SCAFFOLDED: &str = "name: Scaffold Test
+command: ./solve {{part}}
+parts:
+  part1:
+    cases:
+      sample:
+        stdin: ''
+        expected:
+          stdout: ''
+      other:
+        stdin: ''
+        expected:
+          stdout: ''
+  day03:
+    parts:
+      part2:
+        cases:
+          sample:
+            stdin:
+              file: day03/part2/sample/input.txt
+            expected:
+              stdout:
+                file: day03/part2/sample/expected.txt
+";
+
+fn scaffold_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn test_scaffold() {
+    let dir = scaffold_dir("scaffold");
+    let init = init_challenge(&dir, Some("Scaffold Test".to_owned()), None).unwrap();
+    assert_eq!(init.challenge_file, dir.join("challenge.yml"));
+
+    new_part(&dir, "day03/part2").unwrap();
+    let added = new_case(
+        &dir,
+        "day03/part2/sample",
+        NewCaseOptions {
+            stdin_file: true,
+            expected_file: true,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        added.created,
+        [
+            dir.join("day03/part2/sample/input.txt"),
+            dir.join("day03/part2/sample/expected.txt")
+        ]
+    );
+    new_case(&dir, "part1/other", NewCaseOptions::default()).unwrap();
+
+    let source = fs::read_to_string(&init.challenge_file).unwrap();
+    let (schema_comment, body) = source.split_once('\n').unwrap();
+    assert!(schema_comment.starts_with("# yaml-language-server: $schema="));
+    assert_eq!(body, SCAFFOLDED);
+    assert!(check_challenge(&dir).unwrap().is_empty());
+    assert_eq!(list_challenge(&dir).unwrap().name(), "Scaffold Test");
+}
+
+#[test]
+fn test_scaffold_conflicts() {
+    let dir = scaffold_dir("scaffold_conflicts");
+    init_challenge(&dir, None, Some("cat".to_owned())).unwrap();
+    let source = fs::read_to_string(dir.join("challenge.yml")).unwrap();
+    assert!(source.contains("name: scaffold_conflicts\ncommand: cat\n"));
+
+    let err = |res: Result<_, ProgramError>| match res.unwrap_err() {
+        ProgramError::ScaffoldError(err) => err,
+        err => panic!("Expected a scaffolding error, got {err:?}"),
+    };
+    assert!(matches!(
+        err(init_challenge(&dir, None, None)),
+        ScaffoldError::AlreadyExists(_)
+    ));
+    assert!(matches!(
+        err(new_part(&dir, "part1")),
+        ScaffoldError::EntryExists(_)
+    ));
+    assert!(matches!(
+        err(new_part(&dir, "part1/nested")),
+        ScaffoldError::HasCases(_)
+    ));
+    assert!(matches!(
+        err(new_case(&dir, "part1/sample", NewCaseOptions::default())),
+        ScaffoldError::EntryExists(_)
+    ));
+    assert!(matches!(
+        err(new_case(&dir, "loose", NewCaseOptions::default())),
+        ScaffoldError::HasParts(_)
+    ));
+    assert!(matches!(err(new_part(&dir, "/")), ScaffoldError::EmptyPath));
+    assert_eq!(
+        fs::read_to_string(dir.join("challenge.yml")).unwrap(),
+        source
+    );
+}
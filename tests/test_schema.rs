@@ -0,0 +1,15 @@
+use std::fs::read_to_string;
+
+use challenge_script::challenge::challenge_schema;
+
+#[test]
+fn test_checked_in_schema_is_up_to_date() {
+    let generated = serde_json::to_string_pretty(&challenge_schema()).unwrap();
+    let checked_in = read_to_string("./challenge.schema.json").unwrap();
+
+    assert!(
+        checked_in.trim_end() == generated,
+        "challenge.schema.json is out of date. Regenerate it with \
+         `cargo run -- schema > challenge.schema.json`"
+    );
+}
@@ -0,0 +1,90 @@
+use std::fs;
+use std::path::PathBuf;
+
+use challenge_script::challenge::ChallengeParseError;
+use challenge_script::{
+    check_challenge, list_challenge, run_challenges, ChallengeFileError, ChallengeTarget,
+    ListedNode, ProgramError,
+};
+
+fn case_names(target: impl Into<ChallengeTarget>, cases: Vec<String>) -> Vec<String> {
+    let report = run_challenges(target, cases).unwrap();
+    assert!(report.is_success(), "{report:#?}");
+    report
+        .cases
+        .iter()
+        .map(|case| {
+            [case.parent_path.clone(), vec![case.name.clone()]]
+                .concat()
+                .join("/")
+        })
+        .collect()
+}
+
+#[test]
+fn test_workspace() {
+    assert_eq!(
+        case_names("./tests/data/workspace", vec![]),
+        ["2023/day05/sample", "2024/day01/input"]
+    );
+    assert_eq!(
+        case_names("./tests/data/workspace", vec!["2023/day05".to_owned()]),
+        ["2023/day05/sample"]
+    );
+}
+
+#[test]
+fn test_list_workspace() {
+    let tree = list_challenge("./tests/data/workspace/challenge-workspace.yml").unwrap();
+    assert_eq!(tree.name(), "Advent");
+    let ListedNode::Group { parts, .. } = tree else {
+        panic!("Expected the workspace to be a group");
+    };
+    let names = parts.iter().map(ListedNode::name).collect::<Vec<_>>();
+    assert_eq!(names, ["2023", "2024"]);
+}
+
+#[test]
+fn test_check_workspace() {
+    assert!(check_challenge("./tests/data/workspace")
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_workspace_flag() {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("workspace_flag");
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    fs::create_dir_all(dir.join("a")).unwrap();
+    fs::create_dir_all(dir.join("b/c")).unwrap();
+    let challenge =
+        "name: Only\ncommand: echo ok\ncases:\n  only:\n    expected:\n      stdout: ok\n";
+    fs::write(dir.join("a/challenge.yml"), challenge).unwrap();
+    fs::write(dir.join("b/c/challenge.yaml"), challenge).unwrap();
+
+    let err = run_challenges(&dir, vec![]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::InputFileError(ChallengeFileError::FileNotFoundInDirectory(_))
+        ),
+        "{err:?}"
+    );
+    assert_eq!(
+        case_names(ChallengeTarget::Workspace(dir.clone()), vec![]),
+        ["a/only", "b/c/only"]
+    );
+
+    let empty = dir.join("a");
+    fs::remove_file(empty.join("challenge.yml")).unwrap();
+    let err = run_challenges(ChallengeTarget::Workspace(empty), vec![]).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ProgramError::ParseError(ChallengeParseError::EmptyWorkspace(_))
+        ),
+        "{err:?}"
+    );
+}
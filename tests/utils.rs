@@ -1,19 +1,24 @@
 #[macro_export]
 macro_rules! test_challenge {
     ($path:literal, $($parts:literal),+) => {
-        run_challenge($path, vec![$($parts.to_owned()),+]).unwrap();
+        run_challenge($path, vec![$($parts.to_owned()),+])
+            .unwrap()
+            .into_result()
+            .unwrap();
     };
 }
 
 #[macro_export]
 macro_rules! test_challenges {
     ($path:literal, $($parts:literal),+) => {
-        run_challenges($path, vec![$($parts.to_owned()),+]).unwrap();
+        let report = run_challenges($path, vec![$($parts.to_owned()),+]).unwrap();
+        assert!(report.is_success(), "{report:#?}");
     };
 }
 #[macro_export]
 macro_rules! test_all_challenges {
     ($path:literal) => {
-        run_challenges($path, vec![]).unwrap();
+        let report = run_challenges($path, vec![]).unwrap();
+        assert!(report.is_success(), "{report:#?}");
     };
 }